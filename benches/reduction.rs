@@ -0,0 +1,74 @@
+// Compares the reduction algorithms available for P and N
+//
+// Run with: cargo bench --bench reduction
+//
+// Uses std::time::Instant rather than a benchmarking crate, the numbers are
+// only meant for comparing the algorithms against each other.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rust_bitcoin_ecdsa::helpers::montgomery::MontgomeryContext;
+use rust_bitcoin_ecdsa::helpers::utils::{Reduction, reduce_with};
+use rust_bitcoin_ecdsa::{N, P};
+
+const ITERATIONS: u32 = 2_000;
+
+// xorshift64, good enough to generate inputs without pulling in a rand crate
+fn random_bytes<const L: usize>(state: &mut u64) -> [u8; L] {
+    let mut bytes = [0u8; L];
+    for byte in bytes.iter_mut() {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *byte = *state as u8;
+    }
+    bytes
+}
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let mut state = 0x2545F4914F6CDD1D;
+    let wide: [u8; 64] = random_bytes(&mut state);
+
+    for (name, modulus) in [("P", P), ("N", N)] {
+        println!("reducing a 512-bit product mod {}", name);
+        for reduction in [
+            Reduction::SpecialForm,
+            Reduction::Barrett,
+            Reduction::Montgomery,
+            Reduction::LongDivision,
+        ] {
+            if reduction == Reduction::SpecialForm && modulus != P {
+                continue;
+            }
+            let elapsed = time(|| {
+                black_box(reduce_with(black_box(wide), &modulus, reduction).unwrap());
+            });
+            println!("  {:?}: {:?}", reduction, elapsed);
+        }
+    }
+
+    // In Montgomery form a multiplication only needs one redc, no conversion
+    for (name, context) in [
+        ("P", MontgomeryContext::for_p()),
+        ("N", MontgomeryContext::for_n()),
+    ] {
+        // Clearing the top bit of every byte keeps the values below the modulus
+        let a = context.to_montgomery(&random_bytes::<32>(&mut state).map(|b| b & 0x7F));
+        let b = context.to_montgomery(&random_bytes::<32>(&mut state).map(|b| b & 0x7F));
+        let elapsed = time(|| {
+            black_box(context.mont_mul(black_box(&a), black_box(&b)));
+        });
+        println!(
+            "mont_mul mod {} (values already in Montgomery form): {:?}",
+            name, elapsed
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::arithmetic_operations::{add_mod, sub_mod};
+use crate::helpers::limbs::{self, Limbs, WideLimbs, from_be_bytes};
+use crate::helpers::montgomery::MontgomeryContext;
+use crate::helpers::safegcd::SafeGcdContext;
+use crate::helpers::utils::{BarrettContext, Reduction, reduce_limbs, reduce_modulus_limbs};
+use crate::metrics::{self, Operation};
+use crate::{A, B, GENERATOR_X, GENERATOR_Y, N, P};
+
+/// The parameters of a short Weierstrass curve y² = x³ + ax + b over the integers mod p
+///
+/// Point is generic over this trait, so the same point arithmetic runs over
+/// secp256k1 (the default), secp256r1 or a small curve for working examples
+/// by hand. Each curve is a zero sized marker type, every parameter is a
+/// constant, so nothing is looked up at runtime.
+///
+/// All values are 256-bit big endian integers, smaller curves pad with zeros.
+/// The parameters are checked by the compiler the first time a point on the
+/// curve is created, see validate.
+pub trait CurveParams: Clone + Copy + Debug + PartialEq + Eq {
+    /// The field prime p, must be odd
+    const P: [u8; 32];
+    /// The order of the generator point
+    const N: [u8; 32];
+    /// a in y² = x³ + ax + b, reduced mod p
+    const A: [u8; 32];
+    /// b in y² = x³ + ax + b, reduced mod p
+    const B: [u8; 32];
+    /// X coordinate of the generator point
+    const GENERATOR_X: [u8; 32];
+    /// Y coordinate of the generator point
+    const GENERATOR_Y: [u8; 32];
+    /// The algorithm used to reduce products mod p
+    ///
+    /// Long division works for every modulus, curves with a faster option
+    /// (see Reduction) override it. Barrett and Montgomery contexts for p are
+    /// built at compile time.
+    const REDUCTION: Reduction = Reduction::LongDivision;
+}
+
+/// secp256k1, the curve used by bitcoin (SEC 2, section 2.4.1)
+///
+/// Uses the constants at the top of the crate and the special form reduction
+/// of FieldElement, so points on it cost the same as before curves were generic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl CurveParams for Secp256k1 {
+    const P: [u8; 32] = P;
+    const N: [u8; 32] = N;
+    const A: [u8; 32] = A;
+    const B: [u8; 32] = B;
+    const GENERATOR_X: [u8; 32] = GENERATOR_X;
+    const GENERATOR_Y: [u8; 32] = GENERATOR_Y;
+    const REDUCTION: Reduction = Reduction::SpecialForm;
+}
+
+/// secp256r1, also known as NIST P-256 or prime256v1 (SEC 2, section 2.4.2)
+///
+/// This is the curve used by WebAuthn and TLS. Unlike secp256k1, a is not 0
+/// (it is p - 3). p has a special form too, but a different one from
+/// secp256k1's, so products are reduced with Barrett.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256r1;
+
+impl CurveParams for Secp256r1 {
+    // p = 2^256 - 2^224 + 2^192 + 2^96 - 1
+    const P: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ];
+    const N: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63,
+        0x25, 0x51,
+    ];
+    // a = p - 3
+    const A: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xfc,
+    ];
+    const B: [u8; 32] = [
+        0x5a, 0xc6, 0x35, 0xd8, 0xaa, 0x3a, 0x93, 0xe7, 0xb3, 0xeb, 0xbd, 0x55, 0x76, 0x98, 0x86,
+        0xbc, 0x65, 0x1d, 0x06, 0xb0, 0xcc, 0x53, 0xb0, 0xf6, 0x3b, 0xce, 0x3c, 0x3e, 0x27, 0xd2,
+        0x60, 0x4b,
+    ];
+    const GENERATOR_X: [u8; 32] = [
+        0x6b, 0x17, 0xd1, 0xf2, 0xe1, 0x2c, 0x42, 0x47, 0xf8, 0xbc, 0xe6, 0xe5, 0x63, 0xa4, 0x40,
+        0xf2, 0x77, 0x03, 0x7d, 0x81, 0x2d, 0xeb, 0x33, 0xa0, 0xf4, 0xa1, 0x39, 0x45, 0xd8, 0x98,
+        0xc2, 0x96,
+    ];
+    const GENERATOR_Y: [u8; 32] = [
+        0x4f, 0xe3, 0x42, 0xe2, 0xfe, 0x1a, 0x7f, 0x9b, 0x8e, 0xe7, 0xeb, 0x4a, 0x7c, 0x0f, 0x9e,
+        0x16, 0x2b, 0xce, 0x33, 0x57, 0x6b, 0x31, 0x5e, 0xce, 0xcb, 0xb6, 0x40, 0x68, 0x37, 0xbf,
+        0x51, 0xf5,
+    ];
+    const REDUCTION: Reduction = Reduction::Barrett;
+}
+
+/// Checks that the curve parameters describe a usable curve
+///
+/// - p is odd and larger than 3 (primality is not checked)
+/// - the curve's reduction works for p
+/// - a, b and the generator are reduced mod p
+/// - the curve is not singular: 4a³ + 27b² != 0 mod p
+/// - the generator is on the curve: y² = x³ + ax + b mod p
+/// - the order of the generator is larger than 1
+///
+/// Panics with a message saying which check failed. Point calls this in a
+/// const block, so a wrong constant fails the build.
+pub const fn validate<C: CurveParams>() {
+    let p = from_be_bytes(&C::P);
+    let a = from_be_bytes(&C::A);
+    let b = from_be_bytes(&C::B);
+    let x = from_be_bytes(&C::GENERATOR_X);
+    let y = from_be_bytes(&C::GENERATOR_Y);
+    let n = from_be_bytes(&C::N);
+
+    assert!(
+        p[0] & 1 == 1 && limbs::is_greater_or_equal(&p, &[5, 0, 0, 0]),
+        "p must be odd and larger than 3"
+    );
+    assert!(
+        C::REDUCTION.supports(&C::P),
+        "the curve's reduction does not support p"
+    );
+    assert!(
+        !limbs::is_greater_or_equal(&a, &p) && !limbs::is_greater_or_equal(&b, &p),
+        "a and b are not reduced mod p"
+    );
+    assert!(
+        !limbs::is_greater_or_equal(&x, &p) && !limbs::is_greater_or_equal(&y, &p),
+        "the generator is not reduced mod p"
+    );
+    assert!(
+        limbs::is_greater_or_equal(&n, &[2, 0, 0, 0]),
+        "the order of the generator must be larger than 1"
+    );
+
+    // 4a³ + 27b²
+    let a_cubed = const_mul(&const_mul(&a, &a, &p), &a, &p);
+    let b_squared = const_mul(&b, &b, &p);
+    let discriminant = add_mod(
+        &const_mul(&[4, 0, 0, 0], &a_cubed, &p),
+        &const_mul(&[27, 0, 0, 0], &b_squared, &p),
+        &p,
+    );
+    assert!(!limbs::is_zero(&discriminant), "the curve is singular");
+
+    // x³ + ax + b
+    let x_cubed = const_mul(&const_mul(&x, &x, &p), &x, &p);
+    let right_side = add_mod(&add_mod(&x_cubed, &const_mul(&a, &x, &p), &p), &b, &p);
+    assert!(
+        limbs::eq(&const_mul(&y, &y, &p), &right_side),
+        "the generator is not on the curve"
+    );
+}
+
+// a * b mod p in a const fn, only fast enough for a handful of products
+const fn const_mul(a: &Limbs, b: &Limbs, p: &Limbs) -> Limbs {
+    reduce_modulus_limbs(&limbs::mul_wide(a, b), p)
+}
+
+// Arithmetic mod the curve's p on limbs, used by the point formulas.
+//
+// The modulus and the reduction are constants of the curve, so for secp256k1
+// these compile down to the same code as FieldElement. The Barrett or
+// Montgomery context (whichever the curve uses) and the safegcd context for p
+// are computed at compile time, like the ones for P and N.
+pub(crate) struct CurveField<C>(PhantomData<C>);
+
+impl<C: CurveParams> CurveField<C> {
+    pub(crate) const MODULUS: Limbs = from_be_bytes(&C::P);
+
+    const BARRETT: Option<BarrettContext> = match C::REDUCTION {
+        Reduction::Barrett => Some(BarrettContext::new(C::P)),
+        _ => None,
+    };
+
+    const MONTGOMERY: Option<MontgomeryContext> = match C::REDUCTION {
+        Reduction::Montgomery => Some(MontgomeryContext::new(C::P)),
+        _ => None,
+    };
+
+    const SAFEGCD: SafeGcdContext = SafeGcdContext::new(C::P);
+
+    pub(crate) fn add(a: &Limbs, b: &Limbs) -> Limbs {
+        add_mod(a, b, &Self::MODULUS)
+    }
+
+    pub(crate) fn sub(a: &Limbs, b: &Limbs) -> Limbs {
+        sub_mod(a, b, &Self::MODULUS)
+    }
+
+    pub(crate) fn mul(a: &Limbs, b: &Limbs) -> Limbs {
+        metrics::record(Operation::Multiplication);
+        Self::reduce(&limbs::mul_wide(a, b))
+    }
+
+    pub(crate) fn square(a: &Limbs) -> Limbs {
+        metrics::record(Operation::Squaring);
+        Self::reduce(&limbs::square_wide(a))
+    }
+
+    // a must not be 0, p is prime so every other value has an inverse
+    pub(crate) fn invert(a: &Limbs) -> Limbs {
+        metrics::record(Operation::Inversion);
+        assert!(!limbs::is_zero(a), "0 has no inverse");
+        Self::SAFEGCD.invert_limbs(a)
+    }
+
+    // the curve's reduction, with its context when it needs one
+    fn reduce(x: &WideLimbs) -> Limbs {
+        match (&Self::BARRETT, &Self::MONTGOMERY) {
+            (Some(context), _) => {
+                metrics::record(Operation::Reduction);
+                context.reduce_limbs(x)
+            }
+            (_, Some(context)) => {
+                metrics::record(Operation::Reduction);
+                context.reduce_limbs(x)
+            }
+            _ => reduce_limbs(x, &Self::MODULUS, C::REDUCTION),
+        }
+    }
+}
+
+// y² = x³ + 7 over the integers mod 223, the generator (47, 71) has order 21.
+// Small enough to check every result by hand.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Toy;
+
+#[cfg(test)]
+impl CurveParams for Toy {
+    const P: [u8; 32] = small(223);
+    const N: [u8; 32] = small(21);
+    const A: [u8; 32] = small(0);
+    const B: [u8; 32] = small(7);
+    const GENERATOR_X: [u8; 32] = small(47);
+    const GENERATOR_Y: [u8; 32] = small(71);
+}
+
+// a one byte value as a 256-bit big endian integer
+#[cfg(test)]
+pub(crate) const fn small(value: u8) -> [u8; 32] {
+    let mut bytes = [0x00; 32];
+    bytes[31] = value;
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::limbs::{to_be_bytes, wide_to_be_bytes};
+    use crate::helpers::utils::{random_bytes, reduce_modulus};
+
+    #[test]
+    fn test_curves_are_valid() {
+        // the const block is the real check, this makes sure it is compiled
+        const {
+            validate::<Secp256k1>();
+            validate::<Secp256r1>();
+            validate::<Toy>();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the generator is not on the curve")]
+    fn test_validate_generator_off_curve() {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct OffCurve;
+        impl CurveParams for OffCurve {
+            const P: [u8; 32] = small(223);
+            const N: [u8; 32] = small(21);
+            const A: [u8; 32] = small(0);
+            const B: [u8; 32] = small(7);
+            const GENERATOR_X: [u8; 32] = small(47);
+            const GENERATOR_Y: [u8; 32] = small(72);
+        }
+        validate::<OffCurve>();
+    }
+
+    #[test]
+    #[should_panic(expected = "the curve is singular")]
+    fn test_validate_singular_curve() {
+        // y² = x³ has a cusp at (0, 0)
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct Cusp;
+        impl CurveParams for Cusp {
+            const P: [u8; 32] = small(223);
+            const N: [u8; 32] = small(21);
+            const A: [u8; 32] = small(0);
+            const B: [u8; 32] = small(0);
+            const GENERATOR_X: [u8; 32] = small(1);
+            const GENERATOR_Y: [u8; 32] = small(1);
+        }
+        validate::<Cusp>();
+    }
+
+    #[test]
+    #[should_panic(expected = "the curve's reduction does not support p")]
+    fn test_validate_unsupported_reduction() {
+        // Barrett needs a full size modulus
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct SmallBarrett;
+        impl CurveParams for SmallBarrett {
+            const P: [u8; 32] = small(223);
+            const N: [u8; 32] = small(21);
+            const A: [u8; 32] = small(0);
+            const B: [u8; 32] = small(7);
+            const GENERATOR_X: [u8; 32] = small(47);
+            const GENERATOR_Y: [u8; 32] = small(71);
+            const REDUCTION: Reduction = Reduction::Barrett;
+        }
+        validate::<SmallBarrett>();
+    }
+
+    #[test]
+    fn test_curve_field_contexts() {
+        // P-256 with Montgomery instead of Barrett, to cover the other context
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct MontgomeryP256;
+        impl CurveParams for MontgomeryP256 {
+            const P: [u8; 32] = Secp256r1::P;
+            const N: [u8; 32] = Secp256r1::N;
+            const A: [u8; 32] = Secp256r1::A;
+            const B: [u8; 32] = Secp256r1::B;
+            const GENERATOR_X: [u8; 32] = Secp256r1::GENERATOR_X;
+            const GENERATOR_Y: [u8; 32] = Secp256r1::GENERATOR_Y;
+            const REDUCTION: Reduction = Reduction::Montgomery;
+        }
+
+        assert!(CurveField::<Secp256r1>::BARRETT.is_some());
+        assert!(CurveField::<MontgomeryP256>::MONTGOMERY.is_some());
+        assert!(CurveField::<Toy>::BARRETT.is_none() && CurveField::<Toy>::MONTGOMERY.is_none());
+
+        let x = from_be_bytes(&Secp256r1::GENERATOR_X);
+        let y = from_be_bytes(&Secp256r1::GENERATOR_Y);
+        assert_eq!(
+            CurveField::<MontgomeryP256>::mul(&x, &y),
+            CurveField::<Secp256r1>::mul(&x, &y)
+        );
+    }
+
+    #[test]
+    fn test_curve_field_invert() {
+        let x = from_be_bytes(&Secp256r1::GENERATOR_X);
+        let x_inv = CurveField::<Secp256r1>::invert(&x);
+        assert_eq!(CurveField::<Secp256r1>::mul(&x, &x_inv), [1, 0, 0, 0]);
+
+        // 2 * 112 = 224 = 1 mod 223
+        assert_eq!(CurveField::<Toy>::invert(&[2, 0, 0, 0]), [112, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_curve_field_matches_reduce_modulus() {
+        let mut state = 0x2545F4914F6CDD1D;
+        for _ in 0..20 {
+            let a = reduce_modulus(random_bytes(&mut state), Secp256r1::P);
+            let b = reduce_modulus(random_bytes(&mut state), Secp256r1::P);
+            let (a, b) = (from_be_bytes(&a), from_be_bytes(&b));
+
+            let product = wide_to_be_bytes(&limbs::mul_wide(&a, &b));
+            assert_eq!(
+                to_be_bytes(&CurveField::<Secp256r1>::mul(&a, &b)),
+                reduce_modulus(product, Secp256r1::P)
+            );
+            assert_eq!(
+                CurveField::<Secp256r1>::square(&a),
+                CurveField::<Secp256r1>::mul(&a, &a)
+            );
+        }
+
+        // 200 * 100 = 20000 = 153 mod 223
+        assert_eq!(
+            CurveField::<Toy>::mul(&[200, 0, 0, 0], &[100, 0, 0, 0]),
+            [153, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,477 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::arithmetic_operations::{
+    add_mod, batch_invert_limbs, inverse_mod_with, mul_mod, pow_mod, pow_mod_ct, square_mod,
+    sub_mod,
+};
+use crate::helpers::limbs::{self, Limbs, P_LIMBS, from_be_bytes, to_be_bytes};
+use crate::helpers::utils::{Inversion, Reduction};
+use crate::{ArithmeticError, ParseError, U256};
+
+// (P + 1) / 4, the exponent used to compute square roots
+const SQRT_EXPONENT: Limbs = limbs::shift_right_one(
+    &limbs::shift_right_one(&limbs::add(&P_LIMBS, &[1, 0, 0, 0]).0, 0),
+    0,
+);
+
+// P = 2^256 - 2^32 - 977, so products can always use the special form reduction
+const REDUCTION: Reduction = Reduction::SpecialForm;
+
+/// An element of the secp256k1 finite field (integers mod P)
+///
+/// The value is stored as four 64-bit limbs (see helpers::limbs) and is always
+/// reduced, so it is in the range [0, P-1]. All arithmetic is done mod P,
+/// so callers never have to pass the modulus around.
+#[derive(Clone, Copy)]
+pub struct FieldElement(Limbs);
+
+impl FieldElement {
+    /// The additive identity
+    pub const ZERO: Self = FieldElement([0, 0, 0, 0]);
+
+    /// The multiplicative identity
+    pub const ONE: Self = FieldElement([1, 0, 0, 0]);
+
+    /// Creates a field element from a 256-bit big endian integer, reducing it mod P
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        let value = from_be_bytes(&bytes);
+
+        // P is larger than 2^255, so any 256-bit integer is < 2P and
+        // a single subtraction is enough to reduce it
+        FieldElement(limbs::reduce_once(&value, &P_LIMBS, 0))
+    }
+
+    /// Returns the value as a 256-bit big endian integer in the range [0, P-1]
+    pub const fn to_bytes(&self) -> [u8; 32] {
+        to_be_bytes(&self.0)
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        limbs::is_zero(&self.0)
+    }
+
+    /// Returns self * self, faster than multiplying with itself
+    pub fn square(&self) -> FieldElement {
+        FieldElement(square_mod(&self.0, &P_LIMBS, REDUCTION))
+    }
+
+    /// Raises the element to a public 256-bit (big endian) exponent
+    pub fn pow(&self, exponent: &[u8; 32]) -> FieldElement {
+        FieldElement(pow_mod(
+            &self.0,
+            &from_be_bytes(exponent),
+            &P_LIMBS,
+            REDUCTION,
+        ))
+    }
+
+    /// Raises the element to a secret 256-bit (big endian) exponent
+    ///
+    /// Runs the same sequence of operations for every exponent, see
+    /// arithmetic_operations::pow_ct
+    pub fn pow_ct(&self, exponent: &[u8; 32]) -> FieldElement {
+        FieldElement(pow_mod_ct(
+            &self.0,
+            &from_be_bytes(exponent),
+            &P_LIMBS,
+            REDUCTION,
+        ))
+    }
+
+    /// Returns a square root of the element, or None if it has none
+    ///
+    /// P = 3 mod 4 for secp256k1, so if a root exists it is a^((P + 1) / 4).
+    /// Only half of the field elements are squares (quadratic residues), so
+    /// the candidate is squared to check it really is a root.
+    ///
+    /// The other root is the negation of the returned value. This is what is
+    /// needed to recover y from x (y² = x³ + 7) for compressed public keys.
+    pub fn sqrt(&self) -> Option<FieldElement> {
+        let root = FieldElement(pow_mod(&self.0, &SQRT_EXPONENT, &P_LIMBS, REDUCTION));
+        if root.square() == *self {
+            Some(root)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the multiplicative inverse a^-1, so that a * a^-1 = 1 mod P
+    ///
+    /// This is what makes field division possible, e.g. the slope of a line
+    /// s = (y2 - y1) / (x2 - x1) = (y2 - y1) * (x2 - x1)^-1
+    ///
+    /// Returns DivisionByZero for 0
+    ///
+    /// Runs in constant time, using the default Inversion (safegcd)
+    pub fn invert(&self) -> Result<FieldElement, ArithmeticError> {
+        self.invert_with(Inversion::default())
+    }
+
+    /// Same as invert, with a chosen algorithm (see Inversion)
+    pub fn invert_with(&self, inversion: Inversion) -> Result<FieldElement, ArithmeticError> {
+        inverse_mod_with(&self.0, &P_LIMBS, inversion, REDUCTION).map(FieldElement)
+    }
+
+    /// Inverts every element of the slice in place, using a single inversion
+    ///
+    /// Zero elements stay 0, see arithmetic_operations::batch_invert_limbs
+    pub fn batch_invert(elements: &mut [FieldElement]) {
+        let mut values: Vec<Limbs> = elements.iter().map(|element| element.0).collect();
+        batch_invert_limbs(&mut values, &P_LIMBS, REDUCTION);
+        for (element, value) in elements.iter_mut().zip(values) {
+            element.0 = value;
+        }
+    }
+}
+
+// Compared in constant time, see limbs::ct_eq
+impl PartialEq for FieldElement {
+    fn eq(&self, other: &FieldElement) -> bool {
+        limbs::ct_eq(&self.0, &other.0) == 1
+    }
+}
+
+impl Eq for FieldElement {}
+
+/// Parses a decimal or 0x hex value in [0, P-1], see U256::parse_below
+impl FromStr for FieldElement {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        U256::parse_below(s, &P_LIMBS).map(FieldElement)
+    }
+}
+
+/// Formats the value in decimal
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&U256::from_limbs(self.0), f)
+    }
+}
+
+/// Formats the value in lowercase hex, {:#x} adds the 0x prefix
+impl fmt::LowerHex for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&U256::from_limbs(self.0), f)
+    }
+}
+
+// All 64 hex digits, e.g. FieldElement(0x00..01)
+impl fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FieldElement({:#066x})", self)
+    }
+}
+
+impl From<u64> for FieldElement {
+    fn from(value: u64) -> Self {
+        FieldElement([value, 0, 0, 0])
+    }
+}
+
+impl Add for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(add_mod(&self.0, &rhs.0, &P_LIMBS))
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(sub_mod(&self.0, &rhs.0, &P_LIMBS))
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(mul_mod(&self.0, &rhs.0, &P_LIMBS, REDUCTION))
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    // -a mod P = (0 - a) mod P, which keeps -0 = 0
+    fn neg(self) -> FieldElement {
+        FieldElement::ZERO - self
+    }
+}
+
+impl AddAssign for FieldElement {
+    fn add_assign(&mut self, rhs: FieldElement) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for FieldElement {
+    fn sub_assign(&mut self, rhs: FieldElement) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for FieldElement {
+    fn mul_assign(&mut self, rhs: FieldElement) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::P;
+
+    #[test]
+    fn test_from_bytes_reduces_mod_p() {
+        assert_eq!(FieldElement::from_bytes(P), FieldElement::ZERO);
+
+        // 2^256 - 1 mod P = 2^32 + 976
+        let reduced = FieldElement::from_bytes([0xFF; 32]);
+        assert_eq!(reduced, FieldElement::from(0x1000003d0));
+    }
+
+    #[test]
+    fn test_field_addition() {
+        let a = FieldElement::from(3);
+        let b = FieldElement::from(5);
+        assert_eq!(a + b, FieldElement::from(8));
+    }
+
+    #[test]
+    fn test_field_addition_wraps_around_p() {
+        let p_minus_one = FieldElement::ZERO - FieldElement::ONE;
+        assert_eq!(p_minus_one + FieldElement::ONE, FieldElement::ZERO);
+
+        // (P - 1) + (2^32 + 978) = 2^256, which is 2^32 + 977 mod P
+        let sum = p_minus_one + FieldElement::from(0x1000003d2);
+        assert_eq!(sum, FieldElement::from(0x1000003d1));
+    }
+
+    #[test]
+    fn test_field_subtraction_wraps_around_p() {
+        let a = FieldElement::from(2);
+        let b = FieldElement::from(4);
+        let mut p_minus_two = P;
+        p_minus_two[31] -= 2;
+        assert_eq!((a - b).to_bytes(), p_minus_two);
+    }
+
+    #[test]
+    fn test_field_negation() {
+        let a = FieldElement::from(7);
+        assert_eq!(a + -a, FieldElement::ZERO);
+        assert_eq!(-FieldElement::ZERO, FieldElement::ZERO);
+    }
+
+    #[test]
+    fn test_field_multiplication() {
+        let a = FieldElement::from(0xFFFF);
+        let b = FieldElement::from(0x10001);
+        assert_eq!(a * b, FieldElement::from(0xFFFFFFFF));
+    }
+
+    #[test]
+    fn test_field_multiplication_full_size() {
+        // (P - 1) * (P - 1) = (-1) * (-1) = 1
+        let p_minus_one = -FieldElement::ONE;
+        assert_eq!(p_minus_one * p_minus_one, FieldElement::ONE);
+
+        // (P - 1) * 2 = -2
+        let two = FieldElement::from(2);
+        assert_eq!(p_minus_one * two, -two);
+
+        // G_x * G_y mod P, computed independently
+        let x = FieldElement::from_bytes(crate::GENERATOR_X);
+        let y = FieldElement::from_bytes(crate::GENERATOR_Y);
+        let correct_result = FieldElement::from_bytes([
+            0xfd, 0x3d, 0xc5, 0x29, 0xc6, 0xeb, 0x60, 0xfb, 0x9d, 0x16, 0x60, 0x34, 0xcf, 0x3c,
+            0x1a, 0x5a, 0x72, 0x32, 0x4a, 0xa9, 0xdf, 0xd3, 0x42, 0x8a, 0x56, 0xd7, 0xe1, 0xce,
+            0x01, 0x79, 0xfd, 0x9b,
+        ]);
+        assert_eq!(x * y, correct_result);
+        assert_eq!(y * x, correct_result);
+    }
+
+    #[test]
+    fn test_field_square() {
+        let a = FieldElement::from(0xFFFF);
+        assert_eq!(a.square(), FieldElement::from(0xFFFE0001));
+
+        let p_minus_one = -FieldElement::ONE;
+        assert_eq!(p_minus_one.square(), FieldElement::ONE);
+
+        let x = FieldElement::from_bytes(crate::GENERATOR_X);
+        assert_eq!(x.square(), x * x);
+    }
+
+    #[test]
+    fn test_field_pow() {
+        let a = FieldElement::from(3);
+        let exponent = FieldElement::from(40).to_bytes();
+        // 3^40 = 12157665459056928801 = 0xa8b8b452291fe821
+        let correct_result = FieldElement::from(0xa8b8b452291fe821);
+        assert_eq!(a.pow(&exponent), correct_result);
+        assert_eq!(a.pow_ct(&exponent), correct_result);
+    }
+
+    #[test]
+    fn test_sqrt_exponent() {
+        // (P + 1) / 4, computed at compile time
+        let expected = [
+            0x3f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xbf, 0xff, 0xff, 0x0c,
+        ];
+        assert_eq!(to_be_bytes(&SQRT_EXPONENT), expected);
+    }
+
+    #[test]
+    fn test_field_const_construction() {
+        // P reduces to 0 at compile time
+        const ZERO: FieldElement = FieldElement::from_bytes(P);
+        const GX: [u8; 32] = FieldElement::from_bytes(crate::GENERATOR_X).to_bytes();
+        assert!(ZERO.is_zero());
+        assert_eq!(GX, crate::GENERATOR_X);
+    }
+
+    #[test]
+    fn test_sqrt_exponent_small_prime() {
+        // sqrt uses a^((p + 1) / 4), which works for any p = 3 mod 4. With
+        // p = 23 every value can be checked: the squares mod 23 get a root
+        // back, the other 11 values don't.
+        use crate::arithmetic_operations::{pow, square};
+        use crate::curve::small;
+
+        let p = small(23);
+        let exponent = small(6); // (23 + 1) / 4
+        let mut squares = 0;
+        for value in 1..23 {
+            let a = small(value);
+            let root = pow(&a, &exponent, &p);
+            let is_square = (1..23).any(|x| square(&small(x), &p) == a);
+            assert_eq!(square(&root, &p) == a, is_square);
+            if is_square {
+                squares += 1;
+            }
+        }
+        assert_eq!(squares, 11);
+    }
+
+    #[test]
+    fn test_field_sqrt_of_zero_and_one() {
+        assert_eq!(FieldElement::ZERO.sqrt(), Some(FieldElement::ZERO));
+        assert_eq!(FieldElement::ONE.sqrt(), Some(FieldElement::ONE));
+    }
+
+    #[test]
+    fn test_field_sqrt_of_square() {
+        let four = FieldElement::from(4);
+        let root = four.sqrt().unwrap();
+        assert!(root == FieldElement::from(2) || root == -FieldElement::from(2));
+    }
+
+    #[test]
+    fn test_field_sqrt_recovers_generator_y() {
+        // y² = x³ + 7, so the root must be G_y or -G_y
+        let x = FieldElement::from_bytes(crate::GENERATOR_X);
+        let y = FieldElement::from_bytes(crate::GENERATOR_Y);
+        let y_squared = x * x * x + FieldElement::from(7);
+        let root = y_squared.sqrt().unwrap();
+        assert!(root == y || root == -y);
+    }
+
+    #[test]
+    fn test_field_sqrt_of_non_residue() {
+        // 7 and -1 are not squares mod P
+        assert_eq!(FieldElement::from(7).sqrt(), None);
+        assert_eq!((-FieldElement::ONE).sqrt(), None);
+    }
+
+    #[test]
+    fn test_field_inversion() {
+        for value in [1, 2, 3, 7, 255] {
+            let a = FieldElement::from(value);
+            let a_inv = a.invert().unwrap();
+            assert_eq!(a * a_inv, FieldElement::ONE);
+            assert_eq!(a_inv.invert().unwrap(), a);
+        }
+
+        // inverting twice gives back the original value
+        let a = FieldElement::from(0xFFFFFFFF);
+        assert_eq!(a.invert().unwrap().invert().unwrap(), a);
+    }
+
+    #[test]
+    fn test_field_inversion_of_p_minus_one() {
+        // (P - 1)^2 = (-1)^2 = 1, so P - 1 is its own inverse
+        let p_minus_one = -FieldElement::ONE;
+        assert_eq!(p_minus_one.invert().unwrap(), p_minus_one);
+    }
+
+    #[test]
+    fn test_field_inversion_of_zero() {
+        assert!(matches!(
+            FieldElement::ZERO.invert(),
+            Err(ArithmeticError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_field_assign_operations() {
+        let mut a = FieldElement::from(10);
+        a += FieldElement::from(5);
+        assert_eq!(a, FieldElement::from(15));
+        a -= FieldElement::from(3);
+        assert_eq!(a, FieldElement::from(12));
+        a *= FieldElement::from(4);
+        assert_eq!(a, FieldElement::from(48));
+    }
+
+    #[test]
+    fn test_field_batch_invert() {
+        // e.g. the z coordinates of Jacobian points, with the point at infinity's z = 0
+        let x = FieldElement::from_bytes(crate::GENERATOR_X);
+        let y = FieldElement::from_bytes(crate::GENERATOR_Y);
+        let mut elements = [x, FieldElement::ZERO, y, -FieldElement::ONE];
+        FieldElement::batch_invert(&mut elements);
+        assert_eq!(elements[0], x.invert().unwrap());
+        assert_eq!(elements[1], FieldElement::ZERO);
+        assert_eq!(elements[2], y.invert().unwrap());
+        assert_eq!(elements[3], -FieldElement::ONE);
+    }
+
+    #[test]
+    fn test_field_parse_and_format() {
+        // decimal value of G_x from the comment next to GENERATOR_X
+        let x: FieldElement =
+            "55066263022277343669578718895168534326250603453777594175500187360389116729240"
+                .parse()
+                .unwrap();
+        assert_eq!(x, FieldElement::from_bytes(crate::GENERATOR_X));
+        assert_eq!(
+            format!("{:#x}", x),
+            "0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(
+            x.to_string(),
+            "55066263022277343669578718895168534326250603453777594175500187360389116729240"
+        );
+
+        let seven: FieldElement = "0x7".parse().unwrap();
+        assert_eq!(seven, FieldElement::from(7));
+        assert_eq!(
+            format!("{:?}", seven),
+            "FieldElement(0x0000000000000000000000000000000000000000000000000000000000000007)"
+        );
+
+        let p = "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+        assert_eq!(p.parse::<FieldElement>(), Err(ParseError::OutOfRange));
+        assert_eq!("x".parse::<FieldElement>(), Err(ParseError::InvalidDigit));
+    }
+}
@@ -0,0 +1,479 @@
+use crate::{N, P};
+
+// Internal representation of big integers
+//
+// Integers are stored as 64-bit limbs in little endian order, so a 256-bit
+// integer is [u64; 4] with value = limbs[0] + limbs[1] * 2^64 + limbs[2] * 2^128 + ...
+// Products of two limbs fit in a u128, which replaces the byte-by-byte
+// loops with u16 accumulators and does 16 multiplies instead of 1024 for
+// a 256-bit product.
+//
+// The public API still takes and returns big endian [u8; 32], conversion
+// happens at the boundary with from_be_bytes/to_be_bytes.
+//
+// Everything here is a const fn, so derived constants (Barrett's mu, (P + 1) / 4, ...)
+// are computed and checked at compile time. for loops and iterators aren't
+// allowed in a const fn yet, which is why the loops are written with while.
+
+/// A 256-bit integer as 4 little endian 64-bit limbs
+pub type Limbs = [u64; 4];
+
+/// A 512-bit integer (e.g. a full product) as 8 little endian 64-bit limbs
+pub type WideLimbs = [u64; 8];
+
+/// The secp256k1 field prime P as limbs
+pub const P_LIMBS: Limbs = from_be_bytes(&P);
+
+/// The secp256k1 group order N as limbs
+pub const N_LIMBS: Limbs = from_be_bytes(&N);
+
+/// Converts a big endian 256-bit integer to limbs
+pub const fn from_be_bytes(bytes: &[u8; 32]) -> Limbs {
+    let mut limbs = [0u64; 4];
+    let mut i = 0;
+    while i < 4 {
+        let mut limb = 0u64;
+        let mut j = 0;
+        while j < 8 {
+            // limb 0 holds the last 8 bytes
+            limb = (limb << 8) | bytes[(3 - i) * 8 + j] as u64;
+            j += 1;
+        }
+        limbs[i] = limb;
+        i += 1;
+    }
+    limbs
+}
+
+/// Converts limbs back to a big endian 256-bit integer
+pub const fn to_be_bytes(limbs: &Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < 4 {
+        let mut j = 0;
+        while j < 8 {
+            bytes[(3 - i) * 8 + j] = (limbs[i] >> (56 - 8 * j)) as u8;
+            j += 1;
+        }
+        i += 1;
+    }
+    bytes
+}
+
+/// Converts a big endian 512-bit integer to limbs
+pub const fn wide_from_be_bytes(bytes: &[u8; 64]) -> WideLimbs {
+    let (high, low) = bytes.split_at(32);
+    let high = from_be_bytes(high.first_chunk().unwrap());
+    let low = from_be_bytes(low.first_chunk().unwrap());
+    [
+        low[0], low[1], low[2], low[3], high[0], high[1], high[2], high[3],
+    ]
+}
+
+/// Converts 512-bit limbs back to a big endian integer
+pub const fn wide_to_be_bytes(limbs: &WideLimbs) -> [u8; 64] {
+    let high = to_be_bytes(&[limbs[4], limbs[5], limbs[6], limbs[7]]);
+    let low = to_be_bytes(&[limbs[0], limbs[1], limbs[2], limbs[3]]);
+    let mut bytes = [0u8; 64];
+    let mut i = 0;
+    while i < 32 {
+        bytes[i] = high[i];
+        bytes[i + 32] = low[i];
+        i += 1;
+    }
+    bytes
+}
+
+/// Add with carry: returns (a + b + carry) split into the low limb and the carry out
+#[inline(always)]
+pub const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+/// Subtract with borrow: returns (a - b - borrow) as the low limb and the borrow out (0 or 1)
+#[inline(always)]
+pub const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let difference = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (difference as u64, (difference >> 127) as u64)
+}
+
+/// Multiply and accumulate: returns (acc + a * b + carry) as the low limb and the carry out
+///
+/// The largest possible value is (2^64 - 1)^2 + 2 * (2^64 - 1) = 2^128 - 1, so it never overflows
+#[inline(always)]
+pub const fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = acc as u128 + a as u128 * b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+/// a + b, returns the sum mod 2^(64L) and the carry out (0 or 1)
+pub const fn add<const L: usize>(a: &[u64; L], b: &[u64; L]) -> ([u64; L], u64) {
+    let mut result = [0u64; L];
+    let mut carry = 0;
+    let mut i = 0;
+    while i < L {
+        (result[i], carry) = adc(a[i], b[i], carry);
+        i += 1;
+    }
+    (result, carry)
+}
+
+/// a - b, returns the difference mod 2^(64L) and the borrow out (0 or 1)
+pub const fn sub<const L: usize>(a: &[u64; L], b: &[u64; L]) -> ([u64; L], u64) {
+    let mut result = [0u64; L];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < L {
+        (result[i], borrow) = sbb(a[i], b[i], borrow);
+        i += 1;
+    }
+    (result, borrow)
+}
+
+/// return true if: a >= b
+pub const fn is_greater_or_equal<const L: usize>(a: &[u64; L], b: &[u64; L]) -> bool {
+    ct_is_greater_or_equal(a, b) == 1
+}
+
+pub const fn is_zero<const L: usize>(a: &[u64; L]) -> bool {
+    ct_is_zero(a) == 1
+}
+
+/// a == b, usable in const fn unlike ==
+pub const fn eq<const L: usize>(a: &[u64; L], b: &[u64; L]) -> bool {
+    ct_eq(a, b) == 1
+}
+
+/// Multiplies two integers of any number of limbs, keeping the low L limbs of the product
+/// (the full product of A and B limb integers needs A + B limbs)
+pub const fn mul_low<const A: usize, const B: usize, const L: usize>(
+    a: &[u64; A],
+    b: &[u64; B],
+) -> [u64; L] {
+    let mut product = [0u64; L];
+
+    // schoolbook multiplication, row i adds a[i] * b shifted by i limbs
+    let mut i = 0;
+    while i < A && i < L {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < B && i + j < L {
+            (product[i + j], carry) = mac(product[i + j], a[i], b[j], carry);
+            j += 1;
+        }
+        if i + B < L {
+            product[i + B] = carry;
+        }
+        i += 1;
+    }
+
+    product
+}
+
+/// The full 512-bit product of two 256-bit integers
+pub const fn mul_wide(a: &Limbs, b: &Limbs) -> WideLimbs {
+    mul_low(a, b)
+}
+
+/// The full 512-bit square of a 256-bit integer
+///
+/// In a * a every cross product a[i] * a[j] with i != j shows up twice,
+/// (as a[i] * a[j] and a[j] * a[i]), so each is computed once and the sum
+/// doubled, then the squares a[i] * a[i] on the diagonal are added.
+/// That is 6 + 4 = 10 limb multiplies instead of the 16 in mul_wide.
+pub const fn square_wide(a: &Limbs) -> WideLimbs {
+    let mut result = [0u64; 8];
+
+    // cross products a[i] * a[j] for i < j
+    let mut i = 0;
+    while i < 4 {
+        let mut carry = 0;
+        let mut j = i + 1;
+        while j < 4 {
+            (result[i + j], carry) = mac(result[i + j], a[i], a[j], carry);
+            j += 1;
+        }
+        result[i + 4] = carry;
+        i += 1;
+    }
+
+    // double them, the sum is < 2^511 so nothing is shifted out of the top
+    shift_left_one(&mut result, 0);
+
+    // add the diagonal a[i] * a[i] at limb 2i
+    let mut carry = 0;
+    let mut i = 0;
+    while i < 4 {
+        let (low, high) = mac(0, a[i], a[i], 0);
+        (result[2 * i], carry) = adc(result[2 * i], low, carry);
+        (result[2 * i + 1], carry) = adc(result[2 * i + 1], high, carry);
+        i += 1;
+    }
+
+    result
+}
+
+/// Shifts left by one bit, bit (0 or 1) becomes the new LSB
+/// returns the bit that was shifted out of the top
+pub const fn shift_left_one<const L: usize>(a: &mut [u64; L], bit: u64) -> u64 {
+    let mut carry = bit;
+    let mut i = 0;
+    while i < L {
+        let next_carry = a[i] >> 63;
+        a[i] = (a[i] << 1) | carry;
+        carry = next_carry;
+        i += 1;
+    }
+    carry
+}
+
+/// Shifts right by one bit, bit (0 or 1) becomes the new MSB
+pub const fn shift_right_one<const L: usize>(a: &[u64; L], bit: u64) -> [u64; L] {
+    let mut result = [0u64; L];
+    let mut carry = bit;
+    let mut i = L;
+    while i > 0 {
+        i -= 1;
+        result[i] = (a[i] >> 1) | (carry << 63);
+        carry = a[i] & 1;
+    }
+    result
+}
+
+/// Returns bit i of the integer (bit 0 is the LSB)
+pub const fn bit<const L: usize>(a: &[u64; L], i: usize) -> u64 {
+    (a[i / 64] >> (i % 64)) & 1
+}
+
+// Constant-time primitives
+//
+// Anything that touches a private key or a nonce must not branch on it or
+// return early depending on it, otherwise how long an operation takes leaks
+// information about the secret. The functions below take a choice (0 or 1,
+// never a bool, which the compiler likes to turn back into a branch) and
+// turn it into a mask, so the same instructions run whatever the values are.
+
+/// 0 when choice is 0, all ones when choice is 1
+#[inline(always)]
+pub const fn mask(choice: u64) -> u64 {
+    0u64.wrapping_sub(choice)
+}
+
+/// Returns 1 if a >= b and 0 otherwise, without returning early
+pub const fn ct_is_greater_or_equal<const L: usize>(a: &[u64; L], b: &[u64; L]) -> u64 {
+    // a >= b exactly when a - b doesn't borrow
+    1 ^ sub(a, b).1
+}
+
+/// Returns 1 if a is 0 and 0 otherwise, looking at every limb
+pub const fn ct_is_zero<const L: usize>(a: &[u64; L]) -> u64 {
+    let mut bits = 0;
+    let mut i = 0;
+    while i < L {
+        bits |= a[i];
+        i += 1;
+    }
+    // bits | -bits has its top bit set unless bits is 0
+    1 ^ ((bits | bits.wrapping_neg()) >> 63)
+}
+
+/// Returns 1 if a == b and 0 otherwise, looking at every limb
+///
+/// Use this to compare secrets, e.g. checking a secret against an expected
+/// value this way doesn't reveal how many limbs matched. FieldElement and
+/// Scalar implement PartialEq with it.
+pub const fn ct_eq<const L: usize>(a: &[u64; L], b: &[u64; L]) -> u64 {
+    let mut difference = [0u64; L];
+    let mut i = 0;
+    while i < L {
+        difference[i] = a[i] ^ b[i];
+        i += 1;
+    }
+    ct_is_zero(&difference)
+}
+
+/// Returns a when choice is 0 and b when choice is 1
+pub const fn select<const L: usize>(a: &[u64; L], b: &[u64; L], choice: u64) -> [u64; L] {
+    let mask = mask(choice);
+    let mut result = [0u64; L];
+    let mut i = 0;
+    while i < L {
+        result[i] = a[i] ^ (mask & (a[i] ^ b[i]));
+        i += 1;
+    }
+    result
+}
+
+/// Returns a + b when choice is 1 and a when choice is 0, plus the carry out
+pub const fn conditional_add<const L: usize>(
+    a: &[u64; L],
+    b: &[u64; L],
+    choice: u64,
+) -> ([u64; L], u64) {
+    add(a, &select(&[0; L], b, choice))
+}
+
+/// Returns a - b when choice is 1 and a when choice is 0, plus the borrow out
+pub const fn conditional_sub<const L: usize>(
+    a: &[u64; L],
+    b: &[u64; L],
+    choice: u64,
+) -> ([u64; L], u64) {
+    sub(a, &select(&[0; L], b, choice))
+}
+
+/// Subtracts the modulus once if a >= modulus, or if overflow is 1
+///
+/// overflow is an extra top bit of a (e.g. the carry out of an addition),
+/// in which case a really is a + 2^(64L) and the wrapping subtraction
+/// gives the right answer. Brings any value < 2 * modulus into range.
+pub const fn reduce_once<const L: usize>(
+    a: &[u64; L],
+    modulus: &[u64; L],
+    overflow: u64,
+) -> [u64; L] {
+    let (difference, borrow) = sub(a, modulus);
+    // keep the difference if it didn't go negative or if a had the extra bit
+    select(a, &difference, overflow | (1 ^ borrow))
+}
+
+/// Swaps a and b when choice is 1, leaves them untouched when choice is 0
+pub const fn conditional_swap(a: &mut Limbs, b: &mut Limbs, choice: u64) {
+    let mask = mask(choice);
+    let mut i = 0;
+    while i < 4 {
+        let t = mask & (a[i] ^ b[i]);
+        a[i] ^= t;
+        b[i] ^= t;
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::utils::random_bytes;
+
+    #[test]
+    fn test_byte_conversion_round_trip() {
+        let mut state = 0x9E3779B97F4A7C15;
+        for _ in 0..20 {
+            let bytes: [u8; 32] = random_bytes(&mut state);
+            assert_eq!(to_be_bytes(&from_be_bytes(&bytes)), bytes);
+
+            let wide: [u8; 64] = random_bytes(&mut state);
+            assert_eq!(wide_to_be_bytes(&wide_from_be_bytes(&wide)), wide);
+        }
+
+        let mut one = [0u8; 32];
+        one[31] = 0x01;
+        assert_eq!(from_be_bytes(&one), [1, 0, 0, 0]);
+        assert_eq!(
+            P_LIMBS,
+            [
+                0xFFFFFFFEFFFFFC2F,
+                0xFFFFFFFFFFFFFFFF,
+                0xFFFFFFFFFFFFFFFF,
+                0xFFFFFFFFFFFFFFFF
+            ]
+        );
+    }
+
+    #[test]
+    fn test_add_and_sub_carry() {
+        let max = [u64::MAX; 4];
+        let one = [1, 0, 0, 0];
+        assert_eq!(add(&max, &one), ([0; 4], 1));
+        assert_eq!(sub(&[0; 4], &one), (max, 1));
+        assert_eq!(
+            sub(&max, &one),
+            ([u64::MAX - 1, u64::MAX, u64::MAX, u64::MAX], 0)
+        );
+    }
+
+    #[test]
+    fn test_mul_wide() {
+        // (2^256 - 1)^2 = 2^512 - 2^257 + 1
+        let max = [u64::MAX; 4];
+        assert_eq!(
+            mul_wide(&max, &max),
+            [1, 0, 0, 0, u64::MAX - 1, u64::MAX, u64::MAX, u64::MAX]
+        );
+        assert_eq!(
+            mul_wide(&[3, 0, 0, 0], &[5, 0, 0, 0]),
+            [15, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_square_wide_matches_mul_wide() {
+        let max = [u64::MAX; 4];
+        assert_eq!(square_wide(&max), mul_wide(&max, &max));
+        assert_eq!(square_wide(&[0; 4]), [0; 8]);
+
+        let mut state = 0x6A09E667F3BCC908;
+        for _ in 0..100 {
+            let a = from_be_bytes(&random_bytes(&mut state));
+            assert_eq!(square_wide(&a), mul_wide(&a, &a));
+        }
+    }
+
+    #[test]
+    fn test_shifts() {
+        let mut a = [u64::MAX, 0, 0, 1 << 63];
+        assert_eq!(shift_left_one(&mut a, 1), 1);
+        assert_eq!(a, [u64::MAX, 1, 0, 0]);
+        assert_eq!(shift_right_one(&a, 1), [u64::MAX, 0, 0, 1 << 63]);
+    }
+
+    #[test]
+    fn test_ct_comparisons() {
+        let one = [1, 0, 0, 0];
+        let big = [0, 0, 0, 1];
+        assert_eq!(ct_is_greater_or_equal(&big, &one), 1);
+        assert_eq!(ct_is_greater_or_equal(&one, &big), 0);
+        assert_eq!(ct_is_greater_or_equal(&one, &one), 1);
+
+        assert_eq!(ct_is_zero(&[0u64; 4]), 1);
+        assert_eq!(ct_is_zero(&big), 0);
+        assert_eq!(ct_is_zero(&[0, 0, 0, u64::MAX]), 0);
+
+        assert_eq!(ct_eq(&one, &one), 1);
+        assert_eq!(ct_eq(&one, &big), 0);
+    }
+
+    #[test]
+    fn test_select_and_conditional_operations() {
+        let a = [1, 2, 3, 4];
+        let b = [5, 6, 7, 8];
+        assert_eq!(select(&a, &b, 0), a);
+        assert_eq!(select(&a, &b, 1), b);
+
+        assert_eq!(conditional_add(&a, &b, 0), (a, 0));
+        assert_eq!(conditional_add(&a, &b, 1), ([6, 8, 10, 12], 0));
+        assert_eq!(conditional_sub(&b, &a, 0), (b, 0));
+        assert_eq!(conditional_sub(&b, &a, 1), ([4, 4, 4, 4], 0));
+
+        let mut x = a;
+        let mut y = b;
+        conditional_swap(&mut x, &mut y, 0);
+        assert_eq!((x, y), (a, b));
+        conditional_swap(&mut x, &mut y, 1);
+        assert_eq!((x, y), (b, a));
+    }
+
+    #[test]
+    fn test_reduce_once() {
+        let p_plus_one = add(&P_LIMBS, &[1, 0, 0, 0]).0;
+        assert_eq!(reduce_once(&p_plus_one, &P_LIMBS, 0), [1, 0, 0, 0]);
+        assert_eq!(reduce_once(&P_LIMBS, &P_LIMBS, 0), [0; 4]);
+        assert_eq!(reduce_once(&[7, 0, 0, 0], &P_LIMBS, 0), [7, 0, 0, 0]);
+
+        // 2^256 + 1 mod P = 2^32 + 978
+        assert_eq!(
+            reduce_once(&[1, 0, 0, 0], &P_LIMBS, 1),
+            [0x1000003d2, 0, 0, 0]
+        );
+    }
+}
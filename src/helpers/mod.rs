@@ -1 +1,4 @@
+pub(crate) mod limbs;
+pub mod montgomery;
+pub mod safegcd;
 pub mod utils;
@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+
+use crate::helpers::limbs::{self, Limbs, WideLimbs, from_be_bytes, to_be_bytes};
+use crate::helpers::utils::div_rem_limbs;
+use crate::{N, P};
+
+/// Precomputed values for Montgomery multiplication mod a fixed odd 256-bit modulus
+///
+/// In Montgomery form a value a is stored as aR mod m, with R = 2^256.
+/// Multiplying two values in that form with mont_mul gives (aR * bR) / R = abR,
+/// so the result stays in Montgomery form. Dividing by R is cheap (it's a shift)
+/// once enough multiples of m have been added to clear the low 256 bits, so the
+/// full 512-bit division of a normal reduction is never needed.
+///
+/// Converting in and out costs a mont_mul each, so the saving only shows when
+/// values stay in Montgomery form across many mont_mul calls. reduce (and
+/// Reduction::Montgomery) take a plain product and have to convert the result
+/// back, which is a second redc, so they are not faster than Barrett.
+/// Unlike the special form reduction it works for any odd modulus above 2^255.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MontgomeryContext {
+    modulus: Limbs,
+    // R^2 mod m, used to convert values into Montgomery form
+    r_squared: Limbs,
+    // -m^-1 mod 2^64, used to clear one limb of the product at a time
+    m_prime: u64,
+}
+
+impl MontgomeryContext {
+    /// Precomputes R^2 mod m and -m^-1 mod 2^64 for the modulus
+    ///
+    /// The modulus must be odd and have its top bit set (> 2^255), which is true
+    /// for both P and N. That bound is what lets redc finish with exactly two
+    /// conditional subtractions.
+    ///
+    /// This is a const fn, so the contexts for fixed moduli cost nothing at runtime.
+    /// Panics if the modulus is not supported, see supports.
+    pub const fn new(modulus: [u8; 32]) -> Self {
+        assert!(modulus[31] & 1 == 1, "modulus must be odd");
+        assert!(modulus[0] & 0x80 != 0, "modulus must be larger than 2^255");
+        let modulus = from_be_bytes(&modulus);
+
+        // Newton's iteration for the inverse of m mod 2^64, each step doubles the number
+        // of correct bits: 1 -> 2 -> 4 -> ... -> 64 (m is odd so 1 is correct mod 2)
+        let m0 = modulus[0];
+        let mut inverse: u64 = 1;
+        let mut step = 0;
+        while step < 6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(m0.wrapping_mul(inverse)));
+            step += 1;
+        }
+        let m_prime = inverse.wrapping_neg();
+
+        // R^2 mod m = ((2^512 - 1) mod m) + 1
+        let remainder = match div_rem_limbs(&[u64::MAX; 8], &modulus) {
+            Ok((_, remainder)) => remainder,
+            Err(_) => panic!("modulus must not be zero"),
+        };
+        let (mut r_squared, _) = limbs::add(&remainder, &[1, 0, 0, 0]);
+        if limbs::eq(&r_squared, &modulus) {
+            r_squared = [0; 4];
+        }
+
+        MontgomeryContext {
+            modulus,
+            r_squared,
+            m_prime,
+        }
+    }
+
+    /// Whether new accepts the modulus: odd and larger than 2^255
+    pub const fn supports(modulus: &[u8; 32]) -> bool {
+        modulus[31] & 1 == 1 && modulus[0] & 0x80 != 0
+    }
+
+    /// The shared context for the secp256k1 field prime P
+    pub fn for_p() -> &'static MontgomeryContext {
+        static CONTEXT: MontgomeryContext = MontgomeryContext::new(P);
+        &CONTEXT
+    }
+
+    /// The shared context for the secp256k1 group order N
+    pub fn for_n() -> &'static MontgomeryContext {
+        static CONTEXT: MontgomeryContext = MontgomeryContext::new(N);
+        &CONTEXT
+    }
+
+    /// Converts a reduced value a into Montgomery form: aR mod m
+    pub fn to_montgomery(&self, a: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&self.to_montgomery_limbs(&from_be_bytes(a)))
+    }
+
+    /// Converts a value out of Montgomery form: aR -> a mod m
+    pub fn from_montgomery(&self, a: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&self.from_montgomery_limbs(&from_be_bytes(a)))
+    }
+
+    /// Multiplies two values in Montgomery form: (aR, bR) -> abR mod m
+    pub fn mont_mul(&self, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&self.mont_mul_limbs(&from_be_bytes(a), &from_be_bytes(b)))
+    }
+
+    /// reduce 512-bit number to a 256-bits mod the context's modulus
+    ///
+    /// The input and result are plain values, not in Montgomery form, see
+    /// reduce_limbs for what that costs.
+    pub fn reduce(&self, x: [u8; 64]) -> [u8; 32] {
+        to_be_bytes(&self.reduce_limbs(&limbs::wide_from_be_bytes(&x)))
+    }
+
+    pub(crate) const fn to_montgomery_limbs(&self, a: &Limbs) -> Limbs {
+        self.mont_mul_limbs(a, &self.r_squared)
+    }
+
+    // named to match from_montgomery, it is not a constructor
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) const fn from_montgomery_limbs(&self, a: &Limbs) -> Limbs {
+        self.redc(&[a[0], a[1], a[2], a[3], 0, 0, 0, 0])
+    }
+
+    pub(crate) const fn mont_mul_limbs(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        self.redc(&limbs::mul_wide(a, b))
+    }
+
+    // redc divides by R, multiplying by R^2 in Montgomery form puts it back:
+    // mont_mul(x / R, R^2) = (x / R) * R^2 / R = x
+    //
+    // That is two redc per reduction, the price of taking and returning plain values
+    pub(crate) const fn reduce_limbs(&self, x: &WideLimbs) -> Limbs {
+        self.mont_mul_limbs(&self.redc(x), &self.r_squared)
+    }
+
+    // The context for a modulus other than P and N, kept per thread for the
+    // last modulus used so a run of reductions only builds it once
+    pub(crate) fn cached(modulus: &Limbs) -> MontgomeryContext {
+        thread_local! {
+            static LAST: RefCell<Option<MontgomeryContext>> = const { RefCell::new(None) };
+        }
+        LAST.with_borrow_mut(|last| match last {
+            Some(context) if context.modulus == *modulus => context.clone(),
+            _ => last
+                .insert(MontgomeryContext::new(to_be_bytes(modulus)))
+                .clone(),
+        })
+    }
+
+    // Montgomery reduction: x -> x / R mod m
+    //
+    // For each of the 4 low limbs of x (LSB first), add the multiple of m
+    // that turns that limb into 0. After 4 rounds the low 256 bits are all
+    // zero, so dividing by R = 2^256 is just taking the top half.
+    const fn redc(&self, x: &WideLimbs) -> Limbs {
+        // one extra limb for the final carry
+        let mut t = [x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7], 0];
+
+        let mut i = 0;
+        while i < 4 {
+            // u * m clears limb i: t[i] + u * m0 = 0 mod 2^64
+            let u = t[i].wrapping_mul(self.m_prime);
+            let mut carry = 0;
+            let mut j = 0;
+            while j < 4 {
+                (t[i + j], carry) = limbs::mac(t[i + j], u, self.modulus[j], carry);
+                j += 1;
+            }
+            // carry all the way to the top, even once it is 0
+            let mut k = i + 4;
+            while k < 9 {
+                (t[k], carry) = limbs::adc(t[k], carry, 0);
+                k += 1;
+            }
+            i += 1;
+        }
+
+        // t / R is in the top 5 limbs. x < R^2 and m > R / 2, so
+        // t / R < x / R + m < 3m so two subtractions are always enough
+        let modulus = [
+            self.modulus[0],
+            self.modulus[1],
+            self.modulus[2],
+            self.modulus[3],
+            0,
+        ];
+        let result = [t[4], t[5], t[6], t[7], t[8]];
+        let result = limbs::reduce_once(&result, &modulus, 0);
+        let result = limbs::reduce_once(&result, &modulus, 0);
+
+        [result[0], result[1], result[2], result[3]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::utils::{random_bytes, reduce_modulus};
+
+    fn contexts() -> [(&'static MontgomeryContext, [u8; 32]); 2] {
+        [
+            (MontgomeryContext::for_p(), P),
+            (MontgomeryContext::for_n(), N),
+        ]
+    }
+
+    #[test]
+    fn test_m_prime() {
+        // m * m' = -1 mod 2^64
+        for (context, _) in contexts() {
+            let m0 = context.modulus[0];
+            assert_eq!(m0.wrapping_mul(context.m_prime), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_montgomery_round_trip() {
+        let mut state = 0x853C49E6748FEA9B;
+        for (context, modulus) in contexts() {
+            for _ in 0..50 {
+                let wide: [u8; 64] = random_bytes(&mut state);
+                let a = reduce_modulus(wide, modulus);
+                let a_mont = context.to_montgomery(&a);
+                assert_eq!(context.from_montgomery(&a_mont), a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mont_mul_matches_multiplication() {
+        let mut state = 0xDA3E39CB94B95BDB;
+        for (context, modulus) in contexts() {
+            for _ in 0..50 {
+                let a = reduce_modulus(random_bytes(&mut state), modulus);
+                let b = reduce_modulus(random_bytes(&mut state), modulus);
+
+                let product = context.from_montgomery(
+                    &context.mont_mul(&context.to_montgomery(&a), &context.to_montgomery(&b)),
+                );
+                let product_limbs = limbs::mul_wide(&from_be_bytes(&a), &from_be_bytes(&b));
+                let expected = reduce_modulus(limbs::wide_to_be_bytes(&product_limbs), modulus);
+                assert_eq!(product, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_reduce_random_inputs() {
+        let mut state = 0x5851F42D4C957F2D;
+        for (context, modulus) in contexts() {
+            for _ in 0..100 {
+                let x: [u8; 64] = random_bytes(&mut state);
+                assert_eq!(context.reduce(x), reduce_modulus(x, modulus));
+            }
+            assert_eq!(
+                context.reduce([0xFF; 64]),
+                reduce_modulus([0xFF; 64], modulus)
+            );
+            assert_eq!(context.reduce([0x00; 64]), [0x00; 32]);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use std::cell::RefCell;
+
+use crate::helpers::limbs::{self, Limbs, from_be_bytes, to_be_bytes};
+use crate::{N, P};
+
+// Constant-time modular inversion with Bernstein-Yang "safegcd"
+// (https://gcd.cr.yp.to/safegcd-20190413.pdf), ported from libsecp256k1's modinv64.
+//
+// The extended GCD of (modulus, a) is computed with "divsteps", a variant of
+// the binary GCD where each step looks only at the lowest bits. 59 divsteps
+// are batched into a 2x2 matrix that is computed from the bottom 64 bits of
+// f and g alone, then applied to the full numbers at once. 10 batches (590
+// divsteps) are always enough for 256-bit inputs, and the same operations
+// are done every time, so unlike the binary GCD in inverse_mod the running
+// time doesn't depend on the value being inverted.
+//
+// Numbers are held as 5 signed 62-bit limbs so the matrix products fit in an i128
+// with room for the sign: value = v[0] + v[1] * 2^62 + ... + v[4] * 2^248
+
+type Signed62 = [i64; 5];
+
+const M62: u64 = u64::MAX >> 2;
+
+// The transition matrix of 59 divsteps, scaled by 2^62:
+// [f', g'] = [u v; q r] * [f, g] / 2^62
+struct Transition {
+    u: i64,
+    v: i64,
+    q: i64,
+    r: i64,
+}
+
+/// Precomputed values for safegcd inversion mod a fixed odd 256-bit modulus
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeGcdContext {
+    modulus: Signed62,
+    // modulus^-1 mod 2^62, used to keep d and e divisible by 2^62
+    modulus_inv62: u64,
+}
+
+impl SafeGcdContext {
+    /// Precomputes the modulus in signed 62-bit limbs and its inverse mod 2^62
+    ///
+    /// The modulus must be odd, which is true for both P and N.
+    pub const fn new(modulus: [u8; 32]) -> Self {
+        assert!(modulus[31] & 1 == 1, "modulus must be odd");
+        let modulus = from_be_bytes(&modulus);
+
+        // Newton's iteration, the same as for MontgomeryContext
+        let m0 = modulus[0];
+        let mut inverse: u64 = 1;
+        let mut step = 0;
+        while step < 6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(m0.wrapping_mul(inverse)));
+            step += 1;
+        }
+
+        SafeGcdContext {
+            modulus: to_signed62(&modulus),
+            modulus_inv62: inverse & M62,
+        }
+    }
+
+    /// The shared context for the secp256k1 field prime P
+    pub fn for_p() -> &'static SafeGcdContext {
+        static CONTEXT: SafeGcdContext = SafeGcdContext::new(P);
+        &CONTEXT
+    }
+
+    /// The shared context for the secp256k1 group order N
+    pub fn for_n() -> &'static SafeGcdContext {
+        static CONTEXT: SafeGcdContext = SafeGcdContext::new(N);
+        &CONTEXT
+    }
+
+    // The context for a modulus other than P and N, kept per thread for the
+    // last modulus used so a run of inversions only builds it once
+    pub(crate) fn cached(modulus: &Limbs) -> SafeGcdContext {
+        thread_local! {
+            static LAST: RefCell<Option<(Limbs, SafeGcdContext)>> = const { RefCell::new(None) };
+        }
+        LAST.with_borrow_mut(|last| match last {
+            Some((last_modulus, context)) if last_modulus == modulus => context.clone(),
+            _ => {
+                let context = SafeGcdContext::new(to_be_bytes(modulus));
+                last.insert((*modulus, context)).1.clone()
+            }
+        })
+    }
+
+    /// Returns a^-1 mod m, a must already be reduced
+    ///
+    /// The modulus must be prime, or at least a must have an inverse.
+    /// 0 has no inverse and comes back as 0.
+    pub fn invert(&self, a: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&self.invert_limbs(&from_be_bytes(a)))
+    }
+
+    /// invert on limbs
+    pub(crate) fn invert_limbs(&self, a: &Limbs) -> Limbs {
+        // Invariants: d * a = f mod m and e * a = g mod m
+        // f ends up as the gcd (+1 or -1), so d is then the inverse (up to its sign)
+        let mut d = [0; 5];
+        let mut e = [1, 0, 0, 0, 0];
+        let mut f = self.modulus;
+        let mut g = to_signed62(a);
+        // zeta = -(delta + 1/2), delta starts at 1/2
+        let mut zeta = -1;
+
+        for _ in 0..10 {
+            let (next_zeta, t) = divsteps_59(zeta, f[0] as u64, g[0] as u64);
+            zeta = next_zeta;
+            self.update_de(&mut d, &mut e, &t);
+            update_fg(&mut f, &mut g, &t);
+        }
+
+        debug_assert!(limbs::is_zero(a) || is_plus_or_minus_one(&f));
+        from_signed62(&self.normalize(&d, f[4]))
+    }
+
+    // [d, e] = t * [d, e] / 2^62 mod m
+    //
+    // t * [d, e] isn't divisible by 2^62, so a multiple of the modulus is
+    // added first that makes the bottom 62 bits zero. d and e stay in the
+    // range (-2m, m).
+    fn update_de(&self, d: &mut Signed62, e: &mut Signed62, t: &Transition) {
+        let m = &self.modulus;
+        let (u, v, q, r) = (t.u, t.v, t.q, t.r);
+
+        // [md, me] start as 0, plus [u, q] if d is negative and [v, r] if e is negative
+        let sd = d[4] >> 63;
+        let se = e[4] >> 63;
+        let mut md = (u & sd) + (v & se);
+        let mut me = (q & sd) + (r & se);
+
+        let mut cd = u as i128 * d[0] as i128 + v as i128 * e[0] as i128;
+        let mut ce = q as i128 * d[0] as i128 + r as i128 * e[0] as i128;
+
+        // correct md and me so that t * [d, e] + m * [md, me] has 62 zero bottom bits
+        md -= (self
+            .modulus_inv62
+            .wrapping_mul(cd as u64)
+            .wrapping_add(md as u64)
+            & M62) as i64;
+        me -= (self
+            .modulus_inv62
+            .wrapping_mul(ce as u64)
+            .wrapping_add(me as u64)
+            & M62) as i64;
+
+        cd += m[0] as i128 * md as i128;
+        ce += m[0] as i128 * me as i128;
+        debug_assert!(cd as u64 & M62 == 0 && ce as u64 & M62 == 0);
+        cd >>= 62;
+        ce >>= 62;
+
+        // the remaining limbs, each output limb is the next input limb (a shift down by 62)
+        for i in 1..5 {
+            cd += u as i128 * d[i] as i128 + v as i128 * e[i] as i128;
+            ce += q as i128 * d[i] as i128 + r as i128 * e[i] as i128;
+            cd += m[i] as i128 * md as i128;
+            ce += m[i] as i128 * me as i128;
+            d[i - 1] = (cd as u64 & M62) as i64;
+            e[i - 1] = (ce as u64 & M62) as i64;
+            cd >>= 62;
+            ce >>= 62;
+        }
+
+        d[4] = cd as i64;
+        e[4] = ce as i64;
+    }
+
+    // Brings d from (-2m, m) into [0, m), negating it if sign is negative
+    fn normalize(&self, d: &Signed62, sign: i64) -> Signed62 {
+        let m = &self.modulus;
+        let mut r = *d;
+
+        // add the modulus if r is negative, then negate if asked to, giving (-m, m)
+        let add_modulus = r[4] >> 63;
+        for i in 0..5 {
+            r[i] += m[i] & add_modulus;
+        }
+        let negate = sign >> 63;
+        for limb in r.iter_mut() {
+            *limb = (*limb ^ negate) - negate;
+        }
+        propagate_carries(&mut r);
+
+        // add the modulus again if r is still negative, giving [0, m)
+        let add_modulus = r[4] >> 63;
+        for i in 0..5 {
+            r[i] += m[i] & add_modulus;
+        }
+        propagate_carries(&mut r);
+
+        r
+    }
+}
+
+// Runs 59 divsteps on the bottom 64 bits of f and g, returning the new zeta
+// and the transition matrix. Every step is done with masks, never a branch.
+fn divsteps_59(mut zeta: i64, f0: u64, g0: u64) -> (i64, Transition) {
+    // start the matrix as the identity scaled by 2^3, so after 59 steps it is scaled by 2^62
+    let (mut u, mut v, mut q, mut r): (u64, u64, u64, u64) = (8, 0, 0, 8);
+    let mut f = f0;
+    let mut g = g0;
+
+    for _ in 3..62 {
+        // masks for zeta < 0 and for g being odd
+        let mut mask1 = (zeta >> 63) as u64;
+        let mask2 = (g & 1).wrapping_neg();
+
+        // x, y, z are f, u, v negated if zeta < 0
+        let x = (f ^ mask1).wrapping_sub(mask1);
+        let y = (u ^ mask1).wrapping_sub(mask1);
+        let z = (v ^ mask1).wrapping_sub(mask1);
+
+        // add them to g, q, r if g is odd
+        g = g.wrapping_add(x & mask2);
+        q = q.wrapping_add(y & mask2);
+        r = r.wrapping_add(z & mask2);
+
+        // zeta < 0 and g odd: swap roles, zeta becomes -zeta - 2, otherwise zeta - 1
+        mask1 &= mask2;
+        zeta = (zeta ^ mask1 as i64) - 1;
+
+        // add g, q, r to f, u, v in the swap case
+        f = f.wrapping_add(g & mask1);
+        u = u.wrapping_add(q & mask1);
+        v = v.wrapping_add(r & mask1);
+
+        g >>= 1;
+        u <<= 1;
+        v <<= 1;
+    }
+
+    (
+        zeta,
+        Transition {
+            u: u as i64,
+            v: v as i64,
+            q: q as i64,
+            r: r as i64,
+        },
+    )
+}
+
+// [f, g] = t * [f, g] / 2^62, exact since the divsteps made the bottom 62 bits zero
+fn update_fg(f: &mut Signed62, g: &mut Signed62, t: &Transition) {
+    let (u, v, q, r) = (t.u, t.v, t.q, t.r);
+
+    let mut cf = u as i128 * f[0] as i128 + v as i128 * g[0] as i128;
+    let mut cg = q as i128 * f[0] as i128 + r as i128 * g[0] as i128;
+    debug_assert!(cf as u64 & M62 == 0 && cg as u64 & M62 == 0);
+    cf >>= 62;
+    cg >>= 62;
+
+    for i in 1..5 {
+        cf += u as i128 * f[i] as i128 + v as i128 * g[i] as i128;
+        cg += q as i128 * f[i] as i128 + r as i128 * g[i] as i128;
+        f[i - 1] = (cf as u64 & M62) as i64;
+        g[i - 1] = (cg as u64 & M62) as i64;
+        cf >>= 62;
+        cg >>= 62;
+    }
+
+    f[4] = cf as i64;
+    g[4] = cg as i64;
+}
+
+// Moves the bits above 62 of each limb into the next limb up
+fn propagate_carries(r: &mut Signed62) {
+    for i in 0..4 {
+        r[i + 1] += r[i] >> 62;
+        r[i] &= M62 as i64;
+    }
+}
+
+fn is_plus_or_minus_one(f: &Signed62) -> bool {
+    let minus_one = [M62 as i64, M62 as i64, M62 as i64, M62 as i64, -1];
+    *f == [1, 0, 0, 0, 0] || *f == minus_one
+}
+
+// Splits 4 64-bit limbs into 5 62-bit limbs
+const fn to_signed62(a: &Limbs) -> Signed62 {
+    [
+        (a[0] & M62) as i64,
+        ((a[0] >> 62 | a[1] << 2) & M62) as i64,
+        ((a[1] >> 60 | a[2] << 4) & M62) as i64,
+        ((a[2] >> 58 | a[3] << 6) & M62) as i64,
+        (a[3] >> 56) as i64,
+    ]
+}
+
+// Joins 5 62-bit limbs (each in [0, 2^62)) back into 4 64-bit limbs
+fn from_signed62(a: &Signed62) -> Limbs {
+    let a = a.map(|limb| limb as u64);
+    [
+        a[0] | a[1] << 62,
+        a[1] >> 2 | a[2] << 60,
+        a[2] >> 4 | a[3] << 58,
+        a[3] >> 6 | a[4] << 56,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arithmetic_operations::inverse_mod_ct;
+    use crate::helpers::limbs::{N_LIMBS, P_LIMBS};
+    use crate::helpers::utils::{Reduction, random_bytes, reduce_modulus};
+
+    fn contexts() -> [(&'static SafeGcdContext, [u8; 32], Reduction); 2] {
+        [
+            (SafeGcdContext::for_p(), P, Reduction::SpecialForm),
+            (SafeGcdContext::for_n(), N, Reduction::Barrett),
+        ]
+    }
+
+    #[test]
+    fn test_modulus_inv62() {
+        for (context, modulus, _) in contexts() {
+            let m0 = from_be_bytes(&modulus)[0];
+            assert_eq!(m0.wrapping_mul(context.modulus_inv62) & M62, 1);
+        }
+        assert_eq!(SafeGcdContext::for_p().modulus_inv62, 0x27C7F6E22DDACACF);
+        assert_eq!(SafeGcdContext::for_n().modulus_inv62, 0x34F20099AA774EC1);
+    }
+
+    #[test]
+    fn test_signed62_round_trip() {
+        for value in [P_LIMBS, N_LIMBS, [u64::MAX; 4], [1, 0, 0, 0]] {
+            assert_eq!(from_signed62(&to_signed62(&value)), value);
+        }
+    }
+
+    #[test]
+    fn test_safegcd_matches_exponentiation() {
+        let mut state = 0xA54FF53A5F1D36F1;
+        for (context, modulus, reduction) in contexts() {
+            let m = from_be_bytes(&modulus);
+            for _ in 0..50 {
+                let a = from_be_bytes(&reduce_modulus(random_bytes(&mut state), modulus));
+                let expected = inverse_mod_ct(&a, &m, reduction).unwrap();
+                assert_eq!(context.invert_limbs(&a), expected);
+            }
+
+            // 1, 2 and m - 1
+            let m_minus_one = limbs::sub(&m, &[1, 0, 0, 0]).0;
+            for a in [[1, 0, 0, 0], [2, 0, 0, 0], m_minus_one] {
+                let expected = inverse_mod_ct(&a, &m, reduction).unwrap();
+                assert_eq!(context.invert_limbs(&a), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_safegcd_of_zero() {
+        assert_eq!(SafeGcdContext::for_p().invert(&[0; 32]), [0; 32]);
+    }
+
+    #[test]
+    fn test_safegcd_small_modulus_round_trip() {
+        // a modulus with a lot of zero limbs still works
+        let mut modulus = [0u8; 32];
+        modulus[31] = 251;
+        let context = SafeGcdContext::new(modulus);
+        for value in 1..251u8 {
+            let mut a = [0u8; 32];
+            a[31] = value;
+            let inverse = context.invert(&a);
+            assert_eq!((value as u32 * inverse[31] as u32) % 251, 1);
+        }
+    }
+}
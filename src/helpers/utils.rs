@@ -1,40 +1,12 @@
-use crate::ArithmeticError;
-use crate::P;
-use crate::arithmetic_operations::{addition, subtract};
-use std::num::Wrapping;
+use std::cell::RefCell;
 
-/// Helper function to compare if array a is >= b
-/// Specifically used to check if 256-bit int is >= P (modulus)
-///
-/// return true if: a >= b
-/// return false if a < b
-pub fn is_greater_or_equal(a: &[u8; 32], b: &[u8; 32]) -> bool {
-    for i in 0..32 {
-        if a[i] > b[i] {
-            return true;
-        }
-        if a[i] < b[i] {
-            return false;
-        }
-    }
-    true
-}
-
-/// Specifically used to check if 512-bit int is >= P (modulus)
-///
-/// return true if: a >= b
-/// return false if a < b
-pub fn is_greater_or_equal_512(a: &[u8; 64], b: &[u8; 64]) -> bool {
-    for i in 0..64 {
-        if a[i] > b[i] {
-            return true;
-        }
-        if a[i] < b[i] {
-            return false;
-        }
-    }
-    true
-}
+use crate::helpers::limbs::{
+    self, Limbs, N_LIMBS, P_LIMBS, WideLimbs, from_be_bytes, to_be_bytes, wide_from_be_bytes,
+    wide_to_be_bytes,
+};
+use crate::helpers::montgomery::MontgomeryContext;
+use crate::metrics::{self, Operation};
+use crate::{ArithmeticError, N, P};
 
 /// Given a dividend and a divisor this function will return q (quotient) and r (remainder)
 /// Similar to regular division, ex: 7/3 = 2 remainder 1, but for 256-bit integers
@@ -46,228 +18,397 @@ pub fn div_rem(
     dividend: &[u8; 32],
     divisor: &[u8; 32],
 ) -> Result<([u8; 32], [u8; 32]), ArithmeticError> {
-    // Error if dividing by zero
-    if *divisor == [0; 32] {
-        return Err(ArithmeticError::DivisionByZero);
-    }
+    // Widen the dividend to 512 bits, the quotient can't be larger than the dividend
+    // so its top 32 bytes will always be zero
+    let mut wide_dividend = [0u8; 64];
+    wide_dividend[32..].copy_from_slice(dividend);
 
-    // Early return if a < b
-    if !is_greater_or_equal(dividend, divisor) {
-        return Ok(([0; 32], *dividend));
-    }
+    let (wide_quotient, remainder) = div_rem_512(&wide_dividend, divisor)?;
+    let quotient: [u8; 32] = wide_quotient[32..64].try_into().unwrap();
 
-    // initialize a 32-byte array of value 1
-    let mut one_array = [0; 32];
-    one_array[31] = 0x01;
+    Ok((quotient, remainder))
+}
 
-    let mut quotient = [0; 32];
-    let mut remainder = *dividend;
+/// Same as div_rem but for a 512-bit dividend, e.g. the full product of two 256-bit integers
+///
+/// Dividend: &[u8; 64]
+/// Divisor: &[u8; 32]
+/// Returns: (q,r) where a = b * q + r, 0 <= r < b
+pub fn div_rem_512(
+    dividend: &[u8; 64],
+    divisor: &[u8; 32],
+) -> Result<([u8; 64], [u8; 32]), ArithmeticError> {
+    let (quotient, remainder) =
+        div_rem_limbs(&wide_from_be_bytes(dividend), &from_be_bytes(divisor))?;
+    Ok((wide_to_be_bytes(&quotient), to_be_bytes(&remainder)))
+}
+
+/// div_rem_512 on limbs
+///
+/// Uses binary long division (shift-and-subtract), the same as long division
+/// on paper but in base 2. Walking the dividend one bit at a time (MSB first):
+/// 1. shift the remainder left by one and bring down the next bit of the dividend
+/// 2. if the remainder is now >= divisor, subtract the divisor and set that bit of the quotient
+///
+/// This takes 512 steps whatever the values are, instead of O(a/b) subtractions,
+/// and the subtraction is done with a mask so no step branches on the values.
+pub(crate) const fn div_rem_limbs(
+    dividend: &WideLimbs,
+    divisor: &Limbs,
+) -> Result<(WideLimbs, Limbs), ArithmeticError> {
+    // Error if dividing by zero
+    if limbs::is_zero(divisor) {
+        return Err(ArithmeticError::DivisionByZero);
+    }
 
-    // this loop will be slow for big numbers
-    // TODO:Optimitize as this currently runs at O(a/b)
-    while is_greater_or_equal(&remainder, divisor) {
-        remainder = subtract(&remainder, divisor, &P, false);
-        quotient = addition(&quotient, &one_array, &P, false);
+    let mut quotient = [0u64; 8];
+    let mut remainder = [0u64; 4];
+
+    let mut i = 512;
+    while i > 0 {
+        i -= 1;
+        // remainder < divisor < 2^256, so after the shift it fits in 257 bits,
+        // overflow holds that 257th bit
+        let overflow = limbs::shift_left_one(&mut remainder, limbs::bit(dividend, i));
+
+        // If the 257th bit was set the true value is remainder + 2^256,
+        // the wrapping subtraction takes care of it
+        let choice = overflow | limbs::ct_is_greater_or_equal(&remainder, divisor);
+        remainder = limbs::conditional_sub(&remainder, divisor, choice).0;
+        quotient[i / 64] |= choice << (i % 64);
     }
 
     Ok((quotient, remainder))
 }
 
 // reduce 512-bit number to a 256-bits mod P
+//
+// The remainder of full_product / modulus is the reduced value
 pub fn reduce_modulus(full_product: [u8; 64], modulus: [u8; 32]) -> [u8; 32] {
-    let mut temp = full_product;
-    print!("temp is {:?}\n", temp);
-    let padding = [0x00; 32];
-    let mut padded_mod = [0u8; 64];
-    padded_mod[..32].copy_from_slice(&padding);
-    padded_mod[32..].copy_from_slice(&modulus);
-    print!("padded mod is {:?}\n", padded_mod);
-    let mut bottom_half: [u8; 32] = temp[32..64].try_into().unwrap();
-    print!("bottom half is {:?}\n", bottom_half);
-    let mut top_half: [u8; 32] = temp[0..32].try_into().unwrap();
-    print!("top half is {:?}\n", top_half);
-
-    // Reduce until top 32 bytes are zero or bottom 32 < modulus
-    let mut count = 0;
-    while top_half != [0; 32] || is_greater_or_equal(&bottom_half, &modulus) {
-        println!("while loop iteration {}\n", count);
-        if is_greater_or_equal_512(&temp, &padded_mod) {
-            println!("temp is larger than padded mod subtract\n");
-            count += 1;
-            temp = subtract_512(&temp, &padded_mod);
-            println!("subtracted, result: {:?}\n", temp);
-        } else {
-            break;
-        }
-        bottom_half = temp[32..64].try_into().unwrap();
-        print!("bottom half in loop {:?}\n", bottom_half);
-        top_half = temp[0..32].try_into().unwrap();
-        print!("top half in loop {:?}\n", top_half);
-    }
+    to_be_bytes(&reduce_modulus_limbs(
+        &wide_from_be_bytes(&full_product),
+        &from_be_bytes(&modulus),
+    ))
+}
 
-    if is_greater_or_equal(&bottom_half, &modulus) {
-        println!("bottom half is greater than mod, subtract\n");
-        bottom_half = subtract(&bottom_half, &modulus, &modulus, false)
+/// reduce_modulus on limbs
+pub(crate) const fn reduce_modulus_limbs(full_product: &WideLimbs, modulus: &Limbs) -> Limbs {
+    match div_rem_limbs(full_product, modulus) {
+        Ok((_, remainder)) => remainder,
+        Err(_) => panic!("modulus must not be zero"),
     }
+}
 
-    println!("returning bottom 32 bytes {:?}\n", bottom_half);
+// 2^256 - P = 2^32 + 977
+const SECP256K1_FOLD: u64 = 0x1000003d1;
 
-    return bottom_half;
+/// reduce 512-bit number to a 256-bits mod P, for the secp256k1 P only
+///
+/// P = 2^256 - 2^32 - 977 so 2^256 = 2^32 + 977 mod P. Splitting the product
+/// into a high and low half, x = high * 2^256 + low, gives
+/// x = high * (2^32 + 977) + low mod P
+///
+/// high * (2^32 + 977) is at most 289 bits, so folding the bits above 2^256 back
+/// in a couple of times, followed by at most one subtraction of P, fully reduces it.
+pub fn reduce_secp256k1(full_product: [u8; 64]) -> [u8; 32] {
+    to_be_bytes(&reduce_secp256k1_limbs(&wide_from_be_bytes(&full_product)))
 }
 
-pub fn barrett_reduce(full_product: [u8; 64], modulus: [u8; 32]) -> [u8; 32] {
-    let mut mu = [0u8; 64];
-    let mut temp_value = [0u8; 64];
-
-    // Precompute mu
-    let mut mu_num = [0u8; 64];
-    mu_num[..32].copy_from_slice(&modulus);
-    mu_num[32..].copy_from_slice(&[0u8; 32]);
-    let mut mu_den = [0u8; 64];
-    mu_den[..32].copy_from_slice(&[0xFF; 32]);
-    mu_den[32..].copy_from_slice(&[0xFF; 32]);
-    println!("precomputing MU");
-    mu = multiply_512(&mu_num, &mu_den);
-    println!("precomputed MU is {:?}", mu);
-
-    // Reduce
-    let mut q = [0u8; 64];
-    q[..32].copy_from_slice(&full_product[32..64]);
-    q[32..].copy_from_slice(&[0u8; 32]);
-    temp_value = multiply_512(&q, &mu);
-    println!("reducing r, temp_value is {:?}", temp_value);
-    let mut r = subtract_512(&full_product, &temp_value);
-    println!("reducing r, subtract 512 {:?}", r);
-    while is_greater_or_equal_512(&r, &padded_mod(modulus)) {
-        r = subtract_512(&r, &padded_mod(modulus));
-    }
-
-    // Return reduced number
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&r[32..64]);
-    result
+/// reduce_secp256k1 on limbs
+pub(crate) const fn reduce_secp256k1_limbs(full_product: &WideLimbs) -> Limbs {
+    let high = [
+        full_product[4],
+        full_product[5],
+        full_product[6],
+        full_product[7],
+    ];
+    let low = [
+        full_product[0],
+        full_product[1],
+        full_product[2],
+        full_product[3],
+    ];
+
+    // First fold leaves an overflow of at most 34 bits, the second at most 1 bit
+    // and the third none, since the low half is small whenever the sum wraps.
+    // All three folds always run (folding in 0 changes nothing) so the time
+    // taken doesn't depend on the product.
+    let (overflow, result) = fold_secp256k1(&high, &low);
+    let (overflow, result) = fold_secp256k1(&[overflow, 0, 0, 0], &result);
+    let (overflow, result) = fold_secp256k1(&[overflow, 0, 0, 0], &result);
+    debug_assert!(overflow == 0);
+
+    limbs::reduce_once(&result, &P_LIMBS, 0)
 }
 
-/// Returns a padded modulus with a [0u8; 32] at the front.
-fn padded_mod(modulus: [u8; 32]) -> [u8; 64] {
-    let mut padded_mod = [0u8; 64];
-    padded_mod[..32].copy_from_slice(&[0u8; 32]);
-    padded_mod[32..].copy_from_slice(&modulus);
+// computes high * (2^32 + 977) + low
+// returns the bits above 2^256 (overflow) and the low 256 bits separately
+const fn fold_secp256k1(high: &Limbs, low: &Limbs) -> (u64, Limbs) {
+    let mut result = [0u64; 4];
+    let mut carry = 0;
+
+    let mut i = 0;
+    while i < 4 {
+        (result[i], carry) = limbs::mac(low[i], high[i], SECP256K1_FOLD, carry);
+        i += 1;
+    }
+
+    (carry, result)
+}
 
-    padded_mod
+/// The algorithms available to reduce a 512-bit product mod a 256-bit modulus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    /// Folds the high half back in using P = 2^256 - 2^32 - 977, only works for P
+    SpecialForm,
+    /// Barrett reduction, see BarrettContext
+    Barrett,
+    /// Montgomery reduction, see MontgomeryContext (a correctness reference here,
+    /// it is not faster than Barrett on plain values)
+    Montgomery,
+    /// Shift-and-subtract long division, works for any modulus but is the slowest
+    LongDivision,
 }
 
-// multiple 2 512-bit u8 arrays
-fn multiply_512(a: &[u8; 64], b: &[u8; 64]) -> [u8; 64] {
-    let mut full_product = [0x00; 64];
-
-    for i in (0..64).rev() {
-        let mut carry = 0;
-        for j in (0..64).rev() {
-            // low byte index
-            let mut index = 63 - i - j;
-            // calculate byte-wise multiplication
-            let mut sum = a[i] as u16 * b[j] as u16 + full_product[index] as u16 + carry as u16;
-            println!("product for a{} * b{} is {}", i, j, sum);
-            full_product[index] = sum & 0xFF;
-            carry = sum >> 8;
-            //  high byte index
-            index = 62 - i - j;
-            sum = full_product[index] + carry;
-            full_product[index] = sum & 0xFF;
-            carry = sum >> 8;
-
-            while carry > 0 && index > 0 {
-                index = index - 1;
-                sum = full_product[index] + carry;
-                full_product[index] = sum & 0xFF;
-                carry = sum >> 8;
-            }
+impl Reduction {
+    /// The fastest available reduction for the modulus
+    ///
+    /// P has a special form which allows a much faster reduction,
+    /// N uses Barrett reduction with its precomputed context and
+    /// any other modulus goes through the general long division
+    pub fn default_for(modulus: &[u8; 32]) -> Self {
+        if *modulus == P {
+            Reduction::SpecialForm
+        } else if *modulus == N {
+            Reduction::Barrett
+        } else {
+            Reduction::LongDivision
         }
     }
 
-    // Normalize carries
-    let mut carry = 0;
-    let mut normalized_product = [0u8; 64];
-    for k in (0..64).rev() {
-        let sum = full_product[k] + carry;
-        full_product[k] = sum & 0xFF;
-        carry = sum >> 8;
-        normalized_product[k] = full_product[k] as u8;
+    /// Whether the reduction works for the modulus
+    ///
+    /// The special form only works for P, Barrett needs a full size modulus,
+    /// Montgomery an odd one above 2^255 and long division any modulus but 0.
+    pub const fn supports(&self, modulus: &[u8; 32]) -> bool {
+        match self {
+            Reduction::SpecialForm => limbs::eq(&from_be_bytes(modulus), &P_LIMBS),
+            Reduction::Barrett => BarrettContext::supports(modulus),
+            Reduction::Montgomery => MontgomeryContext::supports(modulus),
+            Reduction::LongDivision => !limbs::is_zero(&from_be_bytes(modulus)),
+        }
     }
+}
 
-    normalized_product
+/// The constant-time algorithms available to invert mod a prime
+///
+/// Both give the same inverse and both return DivisionByZero for 0, they only
+/// differ in speed. FieldElement::invert_with and Scalar::invert_with take one,
+/// e.g. to compare them in a benchmark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Inversion {
+    /// Fermat's little theorem, a^(m - 2), about 256 squarings and as many multiplications
+    Exponentiation,
+    /// Bernstein-Yang divsteps, see SafeGcdContext, several times faster
+    #[default]
+    SafeGcd,
 }
 
-// subtract 2 512-bit u8 arrays
-fn subtract_512(a: &[u8; 64], b: &[u8; 64]) -> [u8; 64] {
-    let mut result = [0; 64];
-    let mut borrow = 0;
-
-    for i in (0..64).rev() {
-        // cast values as u16 to catch overflow
-        let mut temp = Wrapping(a[i] as u16) - Wrapping(b[i] as u16) - Wrapping(borrow as u16);
-        // If underflow (e.g., 0 - 1 = 65535), add 256 to get the byte result (0) and borrow 1
-        if temp > Wrapping(255) {
-            temp += Wrapping(256);
-            borrow = 1;
-        } else {
-            borrow = 0;
+/// reduce 512-bit number to a 256-bits mod the modulus with the chosen algorithm
+///
+/// P and N use their shared precomputed contexts, any other modulus reuses
+/// the context from the previous call on this thread when it is the same.
+///
+/// Returns UnsupportedModulus if the reduction can't be used with the
+/// modulus, see Reduction::supports
+pub fn reduce_with(
+    full_product: [u8; 64],
+    modulus: &[u8; 32],
+    reduction: Reduction,
+) -> Result<[u8; 32], ArithmeticError> {
+    if !reduction.supports(modulus) {
+        return Err(ArithmeticError::UnsupportedModulus);
+    }
+    Ok(to_be_bytes(&reduce_limbs(
+        &wide_from_be_bytes(&full_product),
+        &from_be_bytes(modulus),
+        reduction,
+    )))
+}
+
+/// reduce_with on limbs
+pub(crate) fn reduce_limbs(
+    full_product: &WideLimbs,
+    modulus: &Limbs,
+    reduction: Reduction,
+) -> Limbs {
+    metrics::record(Operation::Reduction);
+    match reduction {
+        Reduction::SpecialForm => {
+            assert!(
+                *modulus == P_LIMBS,
+                "the special form reduction only works for P"
+            );
+            reduce_secp256k1_limbs(full_product)
         }
-        result[i] = temp.0 as u8;
+        Reduction::Barrett => match *modulus {
+            P_LIMBS => BarrettContext::for_p().reduce_limbs(full_product),
+            N_LIMBS => BarrettContext::for_n().reduce_limbs(full_product),
+            _ => BarrettContext::cached(modulus).reduce_limbs(full_product),
+        },
+        Reduction::Montgomery => match *modulus {
+            P_LIMBS => MontgomeryContext::for_p().reduce_limbs(full_product),
+            N_LIMBS => MontgomeryContext::for_n().reduce_limbs(full_product),
+            _ => MontgomeryContext::cached(modulus).reduce_limbs(full_product),
+        },
+        Reduction::LongDivision => reduce_modulus_limbs(full_product, modulus),
     }
+}
 
-    result
+/// Precomputed values for reducing 512-bit integers mod a fixed 256-bit modulus
+/// with Barrett reduction (Handbook of Applied Cryptography, algorithm 14.42)
+///
+/// Division is slow, so Barrett replaces it with multiplications by
+/// mu = floor(b^2k / m), which only depends on the modulus and is computed once.
+/// Here b = 2^64 (one limb) and k = 4 (limbs in the modulus).
+///
+/// The contexts for P and N are computed at compile time, see for_p and for_n.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BarrettContext {
+    modulus: Limbs,
+    // floor(2^512 / modulus), at most k + 1 = 5 limbs
+    mu: [u64; 5],
 }
 
-// merge 2 u8 arrays into 1 u16 array
-fn combine_u8_arrays_to_u16(a: [u8; 32], b: [u8; 32]) -> [u16; 64] {
-    let mut result = [0u16; 64];
+impl BarrettContext {
+    /// Precomputes mu for the modulus
+    ///
+    /// The modulus must be a full size 256-bit integer (the top byte is not zero),
+    /// which is true for both P and N.
+    ///
+    /// This is a const fn, so the contexts for fixed moduli cost nothing at runtime.
+    /// Panics if the modulus is not supported, see supports.
+    pub const fn new(modulus: [u8; 32]) -> Self {
+        assert!(
+            modulus[0] != 0,
+            "modulus must be a full size 256-bit integer"
+        );
+        let modulus = from_be_bytes(&modulus);
+
+        // 2^512 doesn't fit in 512 bits, but the modulus is odd (or at least not a power of 2)
+        // so floor((2^512 - 1) / m) = floor(2^512 / m)
+        let quotient = match div_rem_limbs(&[u64::MAX; 8], &modulus) {
+            Ok((quotient, _)) => quotient,
+            Err(_) => panic!("modulus must not be zero"),
+        };
+        let mu = [
+            quotient[0],
+            quotient[1],
+            quotient[2],
+            quotient[3],
+            quotient[4],
+        ];
 
-    for i in 0..32 {
-        result[i] = a[i] as u16;
-        result[i + 32] = b[i] as u16;
+        BarrettContext { modulus, mu }
     }
 
-    result
-}
+    /// Whether new accepts the modulus: the top byte is not zero
+    pub const fn supports(modulus: &[u8; 32]) -> bool {
+        modulus[0] != 0
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // The context for a modulus other than P and N, kept per thread for the
+    // last modulus used so a run of reductions only builds it once
+    pub(crate) fn cached(modulus: &Limbs) -> BarrettContext {
+        thread_local! {
+            static LAST: RefCell<Option<BarrettContext>> = const { RefCell::new(None) };
+        }
+        LAST.with_borrow_mut(|last| match last {
+            Some(context) if context.modulus == *modulus => context.clone(),
+            _ => last
+                .insert(BarrettContext::new(to_be_bytes(modulus)))
+                .clone(),
+        })
+    }
 
-    #[test]
-    fn test_is_greater_or_equal() {
-        // 256
-        let x1 = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x01, 0x00, 0x00,
-        ];
-        // 512
-        let y1 = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x02, 0x00, 0x00,
-        ];
+    /// The shared context for the secp256k1 field prime P
+    pub fn for_p() -> &'static BarrettContext {
+        static CONTEXT: BarrettContext = BarrettContext::new(P);
+        &CONTEXT
+    }
 
-        let x2 = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x01, 0x00, 0x00,
-        ];
+    /// The shared context for the secp256k1 group order N
+    pub fn for_n() -> &'static BarrettContext {
+        static CONTEXT: BarrettContext = BarrettContext::new(N);
+        &CONTEXT
+    }
 
-        let y2 = [
-            0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x01, 0x00, 0x00,
+    /// reduce 512-bit number to a 256-bits mod the context's modulus
+    pub fn reduce(&self, x: [u8; 64]) -> [u8; 32] {
+        to_be_bytes(&self.reduce_limbs(&wide_from_be_bytes(&x)))
+    }
+
+    /// reduce on limbs
+    pub(crate) const fn reduce_limbs(&self, x: &WideLimbs) -> Limbs {
+        // q1 = floor(x / b^(k-1)), the top 5 limbs of x
+        let q1 = [x[3], x[4], x[5], x[6], x[7]];
+        // q2 = q1 * mu
+        let q2: [u64; 10] = limbs::mul_low(&q1, &self.mu);
+        // q3 = floor(q2 / b^(k+1)), the top 5 limbs of q2
+        // q3 is an estimate of x / m that is at most 2 too small
+        let q3 = [q2[5], q2[6], q2[7], q2[8], q2[9]];
+
+        // r = (x mod b^(k+1)) - (q3 * m mod b^(k+1))
+        // Working mod b^(k+1) is enough because the true r is < 3m < b^(k+1),
+        // a negative r wraps around mod b^(k+1) and comes out right
+        let r1 = [x[0], x[1], x[2], x[3], x[4]];
+        let r2: [u64; 5] = limbs::mul_low(&q3, &self.modulus);
+        let (r, _) = limbs::sub(&r1, &r2);
+
+        // At most 2 subtractions are needed, both are always done
+        let modulus = [
+            self.modulus[0],
+            self.modulus[1],
+            self.modulus[2],
+            self.modulus[3],
+            0,
         ];
+        let r = limbs::reduce_once(&r, &modulus, 0);
+        let r = limbs::reduce_once(&r, &modulus, 0);
+
+        // Return reduced number
+        [r[0], r[1], r[2], r[3]]
+    }
+}
+
+/// reduce 512-bit number to a 256-bits mod the modulus using Barrett reduction
+///
+/// This recomputes mu on every call, prefer BarrettContext::for_p/for_n or
+/// keeping a BarrettContext around when reducing many values.
+pub fn barrett_reduce(full_product: [u8; 64], modulus: [u8; 32]) -> [u8; 32] {
+    BarrettContext::new(modulus).reduce(full_product)
+}
 
-        assert_eq!(is_greater_or_equal(&x1, &y1), false); // 256 < 512
-        assert_eq!(is_greater_or_equal(&y1, &x1), true); // 512 > 256
-        assert_eq!(is_greater_or_equal(&x2, &x1), true); // 256 = 256
-        assert_eq!(is_greater_or_equal(&y2, &y1), true); // 1.1534 * 10**77  > 512
+/// Generates L pseudo random bytes for tests from a xorshift64 state
+///
+/// Good enough to generate test inputs without pulling in a rand crate,
+/// the state must not be 0
+#[cfg(test)]
+pub(crate) fn random_bytes<const L: usize>(state: &mut u64) -> [u8; L] {
+    let mut bytes = [0u8; L];
+    for byte in bytes.iter_mut() {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *byte = *state as u8;
     }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arithmetic_operations::subtract;
 
     #[test]
     fn test_div_rem_division_by_zero() {
@@ -328,13 +469,13 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x07,
         ];
-        // 3
+        // 1
         let quotient = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x01,
         ];
-        // 1
+        // 0
         let remainder = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -384,9 +525,9 @@ mod tests {
         let mut one = [0; 32];
         one[31] = 0x01;
         // P
-        let dividend = &P;
+        let dividend = P;
         // P - 1
-        let divisor = subtract(&P, &one, &P, false); // P - 1
+        let divisor = subtract(&P, &one, &P); // P - 1
         // 1
         let quotient = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -406,30 +547,97 @@ mod tests {
     }
 
     #[test]
-    fn test_simple_byte_array_512_subtraction_with_carry() {
-        let a: [u8; 64] = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+    fn test_div_rem_large_dividend_small_divisor() {
+        // 3
+        let mut divisor = [0; 32];
+        divisor[31] = 0x03;
+        // P / 3
+        let quotient = [
+            0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55,
+            0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x54,
+            0xff, 0xff, 0xfe, 0xba,
         ];
-        let b: [u8; 64] = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // P % 3 = 1
+        let mut remainder = [0; 32];
+        remainder[31] = 0x01;
+
+        let correct_answer = (quotient, remainder);
+
+        assert_eq!(div_rem(&P, &divisor).unwrap(), correct_answer);
+    }
+
+    #[test]
+    fn test_div_rem_512_by_p() {
+        // 2^512 - 1
+        let dividend = [0xFF; 64];
+        // (2^512 - 1) / P
+        let quotient = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0xd1,
         ];
-        let correct_result: [u8; 64] = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // (2^512 - 1) % P
+        let remainder = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x07, 0xa2,
+            0x00, 0x0e, 0x90, 0xa0,
+        ];
+
+        let correct_answer = (quotient, remainder);
+
+        assert_eq!(div_rem_512(&dividend, &P).unwrap(), correct_answer);
+    }
+
+    #[test]
+    fn test_div_rem_512_division_by_zero() {
+        assert!(div_rem_512(&[0xFF; 64], &[0; 32]).is_err());
+    }
+
+    #[test]
+    fn test_reduce_modulus_full_size_product() {
+        // (2^512 - 1) mod P, the old subtract loop would never finish on this
+        let remainder = [
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x07, 0xa2,
+            0x00, 0x0e, 0x90, 0xa0,
         ];
-        let result = subtract_512(&a, &b);
-        assert_eq!(result, correct_result);
+        assert_eq!(reduce_modulus([0xFF; 64], P), remainder);
+    }
+
+    #[test]
+    fn test_reduce_secp256k1_matches_reduce_modulus() {
+        let mut mixed = [0xFF; 64];
+        mixed[27] = 0xFE;
+        mixed[59] = 0xFE;
+
+        let mut p_times_big = [0u8; 64];
+        p_times_big[..32].copy_from_slice(&P);
+        p_times_big[32..].copy_from_slice(&P);
+
+        let mut small = [0u8; 64];
+        small[63] = 0x2a;
+
+        for value in [[0xFF; 64], [0x00; 64], mixed, p_times_big, small] {
+            assert_eq!(reduce_secp256k1(value), reduce_modulus(value, P));
+        }
+    }
+
+    #[test]
+    fn test_reduce_secp256k1_p_minus_one_squared() {
+        // (P - 1)^2 = (-1)^2 = 1 mod P
+        // (P - 1)^2 = P^2 - 2P + 1, written out in full
+        let p_minus_one_squared = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfd,
+            0xff, 0xff, 0xf8, 0x5c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x07, 0xa4, 0x00, 0x0e, 0x98, 0x44,
+        ];
+        let mut one = [0u8; 32];
+        one[31] = 0x01;
+        assert_eq!(reduce_secp256k1(p_minus_one_squared), one);
     }
 
     #[test]
@@ -443,11 +651,54 @@ mod tests {
         ];
         let modulus = P;
         let result = barrett_reduce(large_number, modulus);
+        // The 0xFF at byte 5 is 0xFF * 2^464, which is well above P and has to be reduced:
+        // 0xFF * 2^464 = 0xFF * 2^208 * (2^32 + 977) mod P
         let correct_result = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0xFF, 0x00, 0x03, 0xCD, 0x2F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0xFF,
         ];
         assert_eq!(result, correct_result);
     }
+
+    #[test]
+    fn test_barrett_mu_for_p() {
+        // floor(2^512 / P) = 2^256 + 2^32 + 977
+        let mu = [0x1000003d1, 0, 0, 0, 1];
+        assert_eq!(BarrettContext::for_p().mu, mu);
+    }
+
+    #[test]
+    fn test_barrett_reduce_random_inputs_mod_p() {
+        let mut state = 0x2545F4914F6CDD1D;
+        let context = BarrettContext::for_p();
+        for _ in 0..200 {
+            let x: [u8; 64] = random_bytes(&mut state);
+            assert_eq!(context.reduce(x), reduce_modulus(x, P));
+        }
+    }
+
+    #[test]
+    fn test_barrett_reduce_random_inputs_mod_n() {
+        let mut state = 0x9E3779B97F4A7C15;
+        let context = BarrettContext::for_n();
+        for _ in 0..200 {
+            let x: [u8; 64] = random_bytes(&mut state);
+            assert_eq!(context.reduce(x), reduce_modulus(x, N));
+        }
+    }
+
+    #[test]
+    fn test_barrett_reduce_edge_cases() {
+        let context = BarrettContext::for_n();
+        let mut n_padded = [0u8; 64];
+        n_padded[32..].copy_from_slice(&N);
+        let mut n_minus_one = n_padded;
+        n_minus_one[63] -= 1;
+
+        assert_eq!(context.reduce([0x00; 64]), [0x00; 32]);
+        assert_eq!(context.reduce(n_padded), [0x00; 32]);
+        assert_eq!(context.reduce(n_minus_one), reduce_modulus(n_minus_one, N));
+        assert_eq!(context.reduce([0xFF; 64]), reduce_modulus([0xFF; 64], N));
+    }
 }
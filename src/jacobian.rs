@@ -0,0 +1,381 @@
+use std::marker::PhantomData;
+
+use crate::curve::{CurveField, CurveParams};
+use crate::helpers::limbs::{self, Limbs, from_be_bytes, to_be_bytes};
+use crate::metrics::{self, Operation};
+use crate::{Point, Secp256k1};
+
+/// A point in Jacobian projective coordinates, for curves with a = 0
+///
+/// (X, Y, Z) stands for the affine point (X / Z², Y / Z³). Affine addition and
+/// doubling need a division (an inversion) to compute the slope every time.
+/// Keeping the denominator in Z instead means the formulas only multiply, and
+/// the single inversion happens in to_affine once all the work is done. An
+/// inversion costs about as much as 100 multiplications, so a scalar
+/// multiplication with hundreds of doublings and additions is much faster.
+///
+/// The same point has many representations, (X, Y, Z) and (λ²X, λ³Y, λZ) are
+/// equal for every λ != 0, so == compares the affine points they stand for.
+/// Z = 0 is the point at infinity.
+///
+/// The formulas are the ones from the Explicit-Formulas Database
+/// (https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html), which
+/// assume a = 0. Using this type with any other curve fails to compile.
+#[derive(Clone, Copy, Debug)]
+pub struct JacobianPoint<C: CurveParams = Secp256k1> {
+    x: Limbs,
+    y: Limbs,
+    z: Limbs,
+    curve: PhantomData<C>,
+}
+
+impl<C: CurveParams> JacobianPoint<C> {
+    // the formulas below are only correct for a = 0
+    const A_IS_ZERO: () = assert!(
+        limbs::is_zero(&from_be_bytes(&C::A)),
+        "JacobianPoint only supports curves with a = 0"
+    );
+
+    /// The point at infinity, (1, 1, 0)
+    pub fn identity() -> Self {
+        let () = Self::A_IS_ZERO;
+        JacobianPoint {
+            x: [1, 0, 0, 0],
+            y: [1, 0, 0, 0],
+            z: [0; 4],
+            curve: PhantomData,
+        }
+    }
+
+    /// Whether this is the point at infinity
+    pub fn is_identity(&self) -> bool {
+        limbs::is_zero(&self.z)
+    }
+
+    /// Converts back to affine coordinates: (X / Z², Y / Z³)
+    ///
+    /// This is the only operation on Jacobian points that needs an inversion.
+    pub fn to_affine(&self) -> Point<C> {
+        if self.is_identity() {
+            return Point::identity();
+        }
+        let z_inverse = CurveField::<C>::invert(&self.z);
+        let z_inverse_squared = CurveField::<C>::square(&z_inverse);
+        let z_inverse_cubed = CurveField::<C>::mul(&z_inverse_squared, &z_inverse);
+        let x = CurveField::<C>::mul(&self.x, &z_inverse_squared);
+        let y = CurveField::<C>::mul(&self.y, &z_inverse_cubed);
+
+        Point {
+            coordinates: Some((to_be_bytes(&x), to_be_bytes(&y))),
+            curve: PhantomData,
+        }
+    }
+
+    /// Point doubling: computes 2P (dbl-2009-l, 2 multiplications and 5 squarings)
+    ///
+    /// The point at infinity and points with Y = 0 double to the point at infinity,
+    /// the formulas take care of both since Z3 = 2 * Y1 * Z1 = 0.
+    pub fn double(&self) -> Self {
+        metrics::record(Operation::PointDoubling);
+        if self.is_identity() {
+            return *self;
+        }
+
+        // A = X1², B = Y1², C = B²
+        let a = CurveField::<C>::square(&self.x);
+        let b = CurveField::<C>::square(&self.y);
+        let c = CurveField::<C>::square(&b);
+
+        // D = 2 * ((X1 + B)² - A - C)
+        let d = CurveField::<C>::sub(
+            &CurveField::<C>::sub(
+                &CurveField::<C>::square(&CurveField::<C>::add(&self.x, &b)),
+                &a,
+            ),
+            &c,
+        );
+        let d = CurveField::<C>::add(&d, &d);
+
+        // E = 3 * A, F = E²
+        let e = CurveField::<C>::add(&CurveField::<C>::add(&a, &a), &a);
+        let f = CurveField::<C>::square(&e);
+
+        // X3 = F - 2 * D
+        let x3 = CurveField::<C>::sub(&f, &CurveField::<C>::add(&d, &d));
+
+        // Y3 = E * (D - X3) - 8 * C
+        let eight_c = CurveField::<C>::add(&c, &c);
+        let eight_c = CurveField::<C>::add(&eight_c, &eight_c);
+        let eight_c = CurveField::<C>::add(&eight_c, &eight_c);
+        let y3 = CurveField::<C>::sub(
+            &CurveField::<C>::mul(&e, &CurveField::<C>::sub(&d, &x3)),
+            &eight_c,
+        );
+
+        // Z3 = 2 * Y1 * Z1
+        let y1_z1 = CurveField::<C>::mul(&self.y, &self.z);
+        let z3 = CurveField::<C>::add(&y1_z1, &y1_z1);
+
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+            curve: PhantomData,
+        }
+    }
+
+    /// Point addition: computes P + Q (add-2007-bl, 11 multiplications and 5 squarings)
+    ///
+    /// Handles every case: either point at infinity, P = Q (doubles) and
+    /// P = -Q (gives the point at infinity).
+    pub fn add(&self, other: &Self) -> Self {
+        if self.is_identity() {
+            return *other;
+        }
+        if other.is_identity() {
+            return *self;
+        }
+
+        // bring both points to the common denominator Z1² * Z2²
+        // U1 = X1 * Z2², U2 = X2 * Z1², S1 = Y1 * Z2³, S2 = Y2 * Z1³
+        let z1_z1 = CurveField::<C>::square(&self.z);
+        let z2_z2 = CurveField::<C>::square(&other.z);
+        let u1 = CurveField::<C>::mul(&self.x, &z2_z2);
+        let u2 = CurveField::<C>::mul(&other.x, &z1_z1);
+        let s1 = CurveField::<C>::mul(&CurveField::<C>::mul(&self.y, &other.z), &z2_z2);
+        let s2 = CurveField::<C>::mul(&CurveField::<C>::mul(&other.y, &self.z), &z1_z1);
+
+        // H = U2 - U1, Z3 = ((Z1 + Z2)² - Z1Z1 - Z2Z2) * H = 2 * Z1 * Z2 * H
+        let h = CurveField::<C>::sub(&u2, &u1);
+        let z1_plus_z2 = CurveField::<C>::add(&self.z, &other.z);
+        let z3 = CurveField::<C>::mul(
+            &CurveField::<C>::sub(
+                &CurveField::<C>::sub(&CurveField::<C>::square(&z1_plus_z2), &z1_z1),
+                &z2_z2,
+            ),
+            &h,
+        );
+
+        self.add_with(&u1, &s1, &s2, &h, z3, other)
+    }
+
+    /// Mixed addition: computes P + Q where Q is an affine point
+    /// (madd-2007-bl, 7 multiplications and 4 squarings)
+    ///
+    /// Q is (X2, Y2, 1) in Jacobian coordinates, so every product with Z2
+    /// disappears. Scalar multiplication adds the same affine point again and
+    /// again, which makes this the addition it uses.
+    pub fn add_affine(&self, other: &Point<C>) -> Self {
+        let Some((x2, y2)) = other.coordinates else {
+            return *self;
+        };
+        let other = JacobianPoint::from(other);
+        if self.is_identity() {
+            return other;
+        }
+        let x2 = from_be_bytes(&x2);
+        let y2 = from_be_bytes(&y2);
+
+        // U1 = X1, S1 = Y1, U2 = X2 * Z1², S2 = Y2 * Z1³
+        let z1_z1 = CurveField::<C>::square(&self.z);
+        let u2 = CurveField::<C>::mul(&x2, &z1_z1);
+        let s2 = CurveField::<C>::mul(&CurveField::<C>::mul(&y2, &self.z), &z1_z1);
+
+        // H = U2 - X1, Z3 = (Z1 + H)² - Z1Z1 - H² = 2 * Z1 * H
+        let h = CurveField::<C>::sub(&u2, &self.x);
+        let z3 = CurveField::<C>::sub(
+            &CurveField::<C>::sub(
+                &CurveField::<C>::square(&CurveField::<C>::add(&self.z, &h)),
+                &z1_z1,
+            ),
+            &CurveField::<C>::square(&h),
+        );
+
+        self.add_with(&self.x, &self.y, &s2, &h, z3, &other)
+    }
+
+    // The end of add and add_affine, once both points have the same denominator
+    //
+    // I = (2H)², J = H * I, r = 2 * (S2 - S1), V = U1 * I
+    // X3 = r² - J - 2V
+    // Y3 = r * (V - X3) - 2 * S1 * J
+    //
+    // H = 0 means both points have the same x. They are then equal (S1 = S2)
+    // or each other's negative, and the formulas would give the point at
+    // infinity either way, so equal points are doubled instead.
+    fn add_with(
+        &self,
+        u1: &Limbs,
+        s1: &Limbs,
+        s2: &Limbs,
+        h: &Limbs,
+        z3: Limbs,
+        other: &Self,
+    ) -> Self {
+        if limbs::is_zero(h) {
+            return if s1 == s2 {
+                other.double()
+            } else {
+                JacobianPoint::identity()
+            };
+        }
+        metrics::record(Operation::PointAddition);
+
+        let two_h = CurveField::<C>::add(h, h);
+        let i = CurveField::<C>::square(&two_h);
+        let j = CurveField::<C>::mul(h, &i);
+        let r = CurveField::<C>::sub(s2, s1);
+        let r = CurveField::<C>::add(&r, &r);
+        let v = CurveField::<C>::mul(u1, &i);
+
+        let x3 = CurveField::<C>::sub(
+            &CurveField::<C>::sub(&CurveField::<C>::square(&r), &j),
+            &CurveField::<C>::add(&v, &v),
+        );
+        let s1_j = CurveField::<C>::mul(s1, &j);
+        let y3 = CurveField::<C>::sub(
+            &CurveField::<C>::mul(&r, &CurveField::<C>::sub(&v, &x3)),
+            &CurveField::<C>::add(&s1_j, &s1_j),
+        );
+
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+            curve: PhantomData,
+        }
+    }
+}
+
+impl<C: CurveParams> From<&Point<C>> for JacobianPoint<C> {
+    /// (x, y) -> (x, y, 1)
+    fn from(point: &Point<C>) -> Self {
+        let Some((x, y)) = point.coordinates else {
+            return JacobianPoint::identity();
+        };
+        let () = Self::A_IS_ZERO;
+        JacobianPoint {
+            x: from_be_bytes(&x),
+            y: from_be_bytes(&y),
+            z: [1, 0, 0, 0],
+            curve: PhantomData,
+        }
+    }
+}
+
+impl<C: CurveParams> From<Point<C>> for JacobianPoint<C> {
+    fn from(point: Point<C>) -> Self {
+        JacobianPoint::from(&point)
+    }
+}
+
+impl<C: CurveParams> PartialEq for JacobianPoint<C> {
+    // (X1, Y1, Z1) = (X2, Y2, Z2) when X1 / Z1² = X2 / Z2² and Y1 / Z1³ = Y2 / Z2³,
+    // checked without dividing: X1 * Z2² = X2 * Z1² and Y1 * Z2³ = Y2 * Z1³
+    fn eq(&self, other: &Self) -> bool {
+        if self.is_identity() || other.is_identity() {
+            return self.is_identity() == other.is_identity();
+        }
+        let z1_z1 = CurveField::<C>::square(&self.z);
+        let z2_z2 = CurveField::<C>::square(&other.z);
+        let x_equal =
+            CurveField::<C>::mul(&self.x, &z2_z2) == CurveField::<C>::mul(&other.x, &z1_z1);
+        let y_equal = CurveField::<C>::mul(&CurveField::<C>::mul(&self.y, &other.z), &z2_z2)
+            == CurveField::<C>::mul(&CurveField::<C>::mul(&other.y, &self.z), &z1_z1);
+        x_equal && y_equal
+    }
+}
+
+impl<C: CurveParams> Eq for JacobianPoint<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{Toy, small};
+
+    #[test]
+    fn test_round_trip() {
+        let g: Point = Point::generator();
+        assert_eq!(JacobianPoint::from(&g).to_affine(), g);
+
+        let identity: Point = Point::identity();
+        assert!(JacobianPoint::from(&identity).is_identity());
+        assert_eq!(JacobianPoint::<Secp256k1>::identity().to_affine(), identity);
+    }
+
+    #[test]
+    fn test_double_matches_affine() {
+        let g: Point = Point::generator();
+        let mut jacobian = JacobianPoint::from(&g);
+        let mut affine = g;
+        for _ in 0..10 {
+            jacobian = jacobian.double();
+            affine = affine.double();
+            assert_eq!(jacobian.to_affine(), affine);
+        }
+
+        // y = 0 doubles to the point at infinity
+        let point = Point::<Toy>::new(small(6), small(0)).unwrap();
+        assert!(JacobianPoint::from(&point).double().is_identity());
+        assert!(JacobianPoint::<Toy>::identity().double().is_identity());
+    }
+
+    #[test]
+    fn test_add_matches_affine() {
+        // every multiple of G on the small curve, with Z != 1 on both sides
+        let g = Point::<Toy>::generator();
+        let two_g = JacobianPoint::from(&g).double();
+        let mut jacobian = JacobianPoint::from(&g);
+        let mut affine = g.clone();
+        for _ in 0..30 {
+            jacobian = jacobian.add(&two_g).add_affine(&g.negate());
+            affine = Point::add(affine, g.clone()).unwrap();
+            assert_eq!(jacobian.to_affine(), affine);
+        }
+
+        let g: Point = Point::generator();
+        let three_g = JacobianPoint::from(&g).double().add_affine(&g);
+        let expected = Point::add(g.double(), g.clone()).unwrap();
+        assert_eq!(three_g.to_affine(), expected);
+        assert_eq!(
+            three_g.add(&JacobianPoint::from(&g)).to_affine(),
+            g.double().double()
+        );
+    }
+
+    #[test]
+    fn test_add_special_cases() {
+        let g = Point::<Toy>::generator();
+        let two_g = JacobianPoint::from(&g).double();
+        let identity = JacobianPoint::<Toy>::identity();
+
+        assert_eq!(identity.add(&two_g), two_g);
+        assert_eq!(two_g.add(&identity), two_g);
+        assert_eq!(identity.add_affine(&g).to_affine(), g);
+        assert_eq!(two_g.add_affine(&Point::identity()), two_g);
+
+        // P + P = 2P, with P in a different representation on each side
+        let four_g = two_g.double();
+        assert_eq!(two_g.add(&two_g), four_g);
+        assert_eq!(two_g.add(&JacobianPoint::from(&two_g.to_affine())), four_g);
+        assert_eq!(
+            JacobianPoint::from(&g).add_affine(&g).to_affine(),
+            g.double()
+        );
+
+        // P + (-P) = O
+        assert!(two_g.add_affine(&two_g.to_affine().negate()).is_identity());
+    }
+
+    #[test]
+    fn test_equality_ignores_representation() {
+        let g = Point::<Toy>::generator();
+        // 2G from doubling has Z = 2 * 71, from the affine point Z = 1
+        let doubled = JacobianPoint::from(&g).double();
+        let from_affine = JacobianPoint::from(&g.double());
+        assert_ne!(doubled.z, from_affine.z);
+        assert_eq!(doubled, from_affine);
+        assert_ne!(doubled, JacobianPoint::from(&g));
+        assert_ne!(doubled, JacobianPoint::identity());
+    }
+}
@@ -1,7 +1,22 @@
+pub mod curve;
+pub mod field;
 pub mod helpers;
+pub mod jacobian;
+pub mod metrics;
+pub mod scalar;
+pub mod uint;
 
-use crate::helpers::utils::{div_rem, is_greater_or_equal};
-use sha2;
+pub use curve::{CurveParams, Secp256k1, Secp256r1};
+pub use field::FieldElement;
+pub use jacobian::JacobianPoint;
+pub use scalar::Scalar;
+pub use uint::{U256, U512, Uint};
+
+use std::marker::PhantomData;
+
+use curve::CurveField;
+use helpers::limbs::{self, from_be_bytes, to_be_bytes};
+use metrics::Operation;
 
 // Elliptic curve formula = y**2 = x**3 + ax + b
 //
@@ -58,197 +73,729 @@ pub const GENERATOR_Y: [u8; 32] = [
 // G constant (generator point) - all calculations start with this point
 pub const G: (&[u8; 32], &[u8; 32]) = (&GENERATOR_X, &GENERATOR_Y);
 
+// Checks on the constants above, run by the compiler. A typo in any of them
+// fails the build instead of producing wrong signatures.
+const _: () = {
+    use helpers::limbs::{self, N_LIMBS, P_LIMBS, from_be_bytes};
+    use helpers::utils::reduce_secp256k1_limbs;
+
+    // P = 2^256 - 2^32 - 977, which wraps around to 0 - (2^32 + 977)
+    let expected_p = limbs::sub(&[0; 4], &[0x1000003d1, 0, 0, 0]).0;
+    assert!(
+        limbs::eq(&P_LIMBS, &expected_p),
+        "P is not 2^256 - 2^32 - 977"
+    );
+
+    // By Hasse's theorem N is within 2 * sqrt(P) of P + 1, so N < P + 1 and
+    // P + 1 - N is at most 129 bits
+    let (difference, borrow) = limbs::sub(&limbs::add(&P_LIMBS, &[1, 0, 0, 0]).0, &N_LIMBS);
+    assert!(
+        borrow == 0 && difference[3] == 0 && difference[2] <= 1,
+        "N is not the curve order"
+    );
+
+    // G is on the curve: y² = x³ + 7 mod P
+    let x = from_be_bytes(&GENERATOR_X);
+    let y = from_be_bytes(&GENERATOR_Y);
+    let b = from_be_bytes(&B);
+    assert!(limbs::is_zero(&from_be_bytes(&A)), "A is not 0");
+    assert!(
+        !limbs::is_greater_or_equal(&x, &P_LIMBS) && !limbs::is_greater_or_equal(&y, &P_LIMBS),
+        "G is not reduced mod P"
+    );
+    let y_squared = reduce_secp256k1_limbs(&limbs::square_wide(&y));
+    let x_squared = reduce_secp256k1_limbs(&limbs::square_wide(&x));
+    let x_cubed = reduce_secp256k1_limbs(&limbs::mul_wide(&x_squared, &x));
+    let right_side = arithmetic_operations::add_mod(&x_cubed, &b, &P_LIMBS);
+    assert!(limbs::eq(&y_squared, &right_side), "G is not on the curve");
+};
+
 #[derive(Debug)]
 pub enum EcdsaError {
     InvalidPointAddition,
+    // the coordinates don't satisfy the curve equation
+    PointNotOnCurve,
 }
 
 #[derive(Debug)]
 pub enum ArithmeticError {
     DivisionByZero,
+    // a and the modulus share a factor, so a has no inverse mod it
+    NotInvertible,
+    // the chosen reduction can't be used with the modulus, see Reduction::supports
+    UnsupportedModulus,
 }
 
-// A point on the secp256k1 curve
+// Errors from parsing integers, field elements and scalars from strings
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    // no digits after the optional 0x prefix
+    Empty,
+    // a character that isn't a digit of the base
+    InvalidDigit,
+    // the value doesn't fit in the integer type
+    Overflow,
+    // the value fits but is not in the allowed range (e.g. a scalar >= N)
+    OutOfRange,
+}
+
+// A point on an elliptic curve, secp256k1 unless another curve is given
+//
+// Point<Secp256r1> is a point on P-256, see CurveParams. The curve is only a
+// type, so a point is still just its two coordinates, or None for the point
+// at infinity which has no coordinates.
 #[derive(Clone, Debug, PartialEq)]
-pub struct Point {
-    x: [u8; 32],
-    y: [u8; 32],
+pub struct Point<C: CurveParams = Secp256k1> {
+    coordinates: Option<([u8; 32], [u8; 32])>,
+    curve: PhantomData<C>,
 }
 
-impl Point {
-    /// The generator point G
+impl<C: CurveParams> Point<C> {
+    /// The generator point G of the curve
     pub fn generator() -> Self {
+        const { curve::validate::<C>() };
+        Point {
+            coordinates: Some((C::GENERATOR_X, C::GENERATOR_Y)),
+            curve: PhantomData,
+        }
+    }
+
+    /// The point at infinity, the identity of point addition: P + O = P
+    pub fn identity() -> Self {
         Point {
-            x: GENERATOR_X,
-            y: GENERATOR_Y,
+            coordinates: None,
+            curve: PhantomData,
         }
     }
 
-    // Scalar multiiplcation - doubles a point
-    pub fn double() -> Self {
-        unimplemented!("Need to implement point doubling")
+    /// Creates a point from its coordinates
+    ///
+    /// Returns PointNotOnCurve unless x and y are reduced mod p and
+    /// satisfy y² = x³ + ax + b
+    pub fn new(x: [u8; 32], y: [u8; 32]) -> Result<Self, EcdsaError> {
+        const { curve::validate::<C>() };
+        let point = Point {
+            coordinates: Some((x, y)),
+            curve: PhantomData,
+        };
+        if point.is_on_curve() {
+            Ok(point)
+        } else {
+            Err(EcdsaError::PointNotOnCurve)
+        }
     }
 
-    // Point addition is a geometric operation which takes 2 points on the curve
-    // P and Q and produces a third point R = P + Q.
-    //
-    // Considerations:
-    //
-    // P and Q are distinct points (P != Q)
-    // 2 other considerations (will handle later)
-    //  1. P = Q --> this is point doubling
-    //  2. One of the points is the point at infinity or (0, 0)
-    pub fn add(p: Point, q: Point) -> Result<Point, EcdsaError> {
-        if p.x == q.x && p.y == q.y {
-            panic!("Use double() for equal points");
+    /// The x coordinate as a 256-bit big endian integer, None for the point at infinity
+    pub fn x(&self) -> Option<[u8; 32]> {
+        self.coordinates.map(|(x, _)| x)
+    }
+
+    /// The y coordinate as a 256-bit big endian integer, None for the point at infinity
+    pub fn y(&self) -> Option<[u8; 32]> {
+        self.coordinates.map(|(_, y)| y)
+    }
+
+    /// Whether this is the point at infinity
+    pub fn is_identity(&self) -> bool {
+        self.coordinates.is_none()
+    }
+
+    /// The point reflected over the x axis: -(x, y) = (x, -y)
+    ///
+    /// P + (-P) is the point at infinity, which is its own negation.
+    pub fn negate(&self) -> Self {
+        let Some((x, y)) = self.coordinates else {
+            return Point::identity();
+        };
+        let minus_y = CurveField::<C>::sub(&[0; 4], &from_be_bytes(&y));
+        Point {
+            coordinates: Some((x, to_be_bytes(&minus_y))),
+            curve: PhantomData,
+        }
+    }
+
+    /// Encodes the point as in SEC 1, section 2.3.3
+    ///
+    /// - the point at infinity is the single byte 0x00
+    /// - compressed: 0x02 (y is even) or 0x03 (y is odd) followed by x, 33 bytes.
+    ///   Only x is stored since y can be recomputed from it, up to its sign.
+    /// - uncompressed: 0x04 followed by x and y, 65 bytes
+    pub fn to_sec1(&self, compressed: bool) -> Vec<u8> {
+        let Some((x, y)) = self.coordinates else {
+            return vec![0x00];
+        };
+        let mut encoded = Vec::with_capacity(65);
+        if compressed {
+            encoded.push(0x02 | (y[31] & 1));
+            encoded.extend_from_slice(&x);
+        } else {
+            encoded.push(0x04);
+            encoded.extend_from_slice(&x);
+            encoded.extend_from_slice(&y);
+        }
+        encoded
+    }
+
+    // checks y² = x³ + ax + b mod p, the point at infinity is always on the curve
+    fn is_on_curve(&self) -> bool {
+        let Some((x, y)) = self.coordinates else {
+            return true;
+        };
+        let p = CurveField::<C>::MODULUS;
+        let x = from_be_bytes(&x);
+        let y = from_be_bytes(&y);
+        if limbs::is_greater_or_equal(&x, &p) || limbs::is_greater_or_equal(&y, &p) {
+            return false;
+        }
+
+        let a = from_be_bytes(&C::A);
+        let b = from_be_bytes(&C::B);
+        let x_cubed = CurveField::<C>::mul(&CurveField::<C>::square(&x), &x);
+        let right_side = CurveField::<C>::add(
+            &CurveField::<C>::add(&x_cubed, &CurveField::<C>::mul(&a, &x)),
+            &b,
+        );
+        CurveField::<C>::square(&y) == right_side
+    }
+
+    /// Point doubling: computes P + P = 2P
+    ///
+    /// Draw the tangent line to the curve at P, it meets the curve at exactly one
+    /// more point. 2P is that point reflected over the x axis. The slope of the
+    /// tangent comes from differentiating y² = x³ + ax + b:
+    ///
+    /// s = (3x² + a) / 2y
+    /// x3 = s² - 2x
+    /// y3 = s(x - x3) - y
+    ///
+    /// When y = 0 the tangent is vertical and never meets the curve again, so
+    /// 2P is the point at infinity. Doubling the point at infinity gives itself.
+    pub fn double(&self) -> Self {
+        metrics::record(Operation::PointDoubling);
+        let Some((x, y)) = self.coordinates else {
+            return Point::identity();
+        };
+        let x = from_be_bytes(&x);
+        let y = from_be_bytes(&y);
+        if limbs::is_zero(&y) {
+            return Point::identity();
+        }
+
+        // s = (3x² + a) / 2y
+        let x_squared = CurveField::<C>::square(&x);
+        let three_x_squared =
+            CurveField::<C>::add(&CurveField::<C>::add(&x_squared, &x_squared), &x_squared);
+        let numerator = CurveField::<C>::add(&three_x_squared, &from_be_bytes(&C::A));
+        let denominator = CurveField::<C>::add(&y, &y);
+        let slope = CurveField::<C>::mul(&numerator, &CurveField::<C>::invert(&denominator));
+
+        // x3 = s² - 2x
+        let x3 = CurveField::<C>::sub(
+            &CurveField::<C>::square(&slope),
+            &CurveField::<C>::add(&x, &x),
+        );
+        // y3 = s(x - x3) - y
+        let y3 = CurveField::<C>::sub(
+            &CurveField::<C>::mul(&slope, &CurveField::<C>::sub(&x, &x3)),
+            &y,
+        );
+
+        Point {
+            coordinates: Some((to_be_bytes(&x3), to_be_bytes(&y3))),
+            curve: PhantomData,
+        }
+    }
+
+    /// Point addition: computes R = P + Q for any two points on the curve
+    ///
+    /// Draw the line through P and Q, it meets the curve at exactly one more
+    /// point. R is that point reflected over the x axis:
+    ///
+    /// s = (y2 - y1) / (x2 - x1)
+    /// x3 = s² - x1 - x2
+    /// y3 = s(x1 - x3) - y1
+    ///
+    /// The cases where that line doesn't exist or is vertical are handled too:
+    ///
+    /// - P or Q is the point at infinity: O + Q = Q and P + O = P
+    /// - P = Q: the line is the tangent at P, see double
+    /// - P = -Q (same x, opposite y): the line is vertical, so R is the point at infinity
+    ///
+    /// Point::new and the point operations only ever build points on the
+    /// curve, so both points are trusted to be on it and this always returns
+    /// Ok. Checking the curve equation for both would cost more than the
+    /// addition itself, it is only asserted in debug builds.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(p: Point<C>, q: Point<C>) -> Result<Point<C>, EcdsaError> {
+        debug_assert!(p.is_on_curve() && q.is_on_curve());
+        Ok(p.add_unchecked(&q))
+    }
+
+    /// Scalar multiplication: computes kP = P + P + ... + P (k times)
+    ///
+    /// Uses double-and-add, the same idea as square-and-multiply in pow_mod:
+    /// for each bit of k (MSB first) double the running result, and add P when
+    /// the bit is 1. That is at most 256 doublings and 256 additions instead
+    /// of k - 1 additions. 0P is the point at infinity.
+    ///
+    /// k is any 256-bit integer and is not reduced: Scalar is mod secp256k1's
+    /// N, which is the wrong order for every other curve. Since the order of
+    /// the generator G is n, kG and (k + n)G are the same point. On secp256k1
+    /// use Point::mul, which takes a Scalar.
+    ///
+    /// NOTE: this is the simple baseline. The number of additions and the
+    /// branches depend on the bits of k, and every step does an inversion
+    /// (JacobianPoint avoids those).
+    pub fn mul_u256(&self, k: &U256) -> Self {
+        if k.is_zero() {
+            return Point::identity();
+        }
+
+        // the top set bit gives P, there is nothing to double before it
+        let mut result = self.clone();
+        for i in (0..k.bits() - 1).rev() {
+            result = result.double();
+            if k.bit(i) {
+                result = result.add_unchecked(self);
+            }
         }
 
-        // compute slope: s = (y2 - y1) / (x2 - x1) mod P
+        result
+    }
+
+    // add for points already known to be on the curve, which is every point
+    // built by Point::new or by the point operations
+    fn add_unchecked(&self, other: &Self) -> Self {
+        let (Some((x1, y1)), Some((x2, y2))) = (self.coordinates, other.coordinates) else {
+            // at least one of them is the point at infinity
+            return if self.is_identity() {
+                other.clone()
+            } else {
+                self.clone()
+            };
+        };
+        if x1 == x2 {
+            // two points with the same x are either equal or each other's negative
+            return if y1 == y2 {
+                self.double()
+            } else {
+                Point::identity()
+            };
+        }
+        metrics::record(Operation::PointAddition);
+
+        let (x1, y1) = (from_be_bytes(&x1), from_be_bytes(&y1));
+        let (x2, y2) = (from_be_bytes(&x2), from_be_bytes(&y2));
+
+        // s = (y2 - y1) / (x2 - x1), x1 != x2 so the denominator is not 0
+        let numerator = CurveField::<C>::sub(&y2, &y1);
+        let denominator = CurveField::<C>::sub(&x2, &x1);
+        let slope = CurveField::<C>::mul(&numerator, &CurveField::<C>::invert(&denominator));
+
+        // x3 = s² - x1 - x2
+        let x3 = CurveField::<C>::sub(
+            &CurveField::<C>::sub(&CurveField::<C>::square(&slope), &x1),
+            &x2,
+        );
+        // y3 = s(x1 - x3) - y1
+        let y3 = CurveField::<C>::sub(
+            &CurveField::<C>::mul(&slope, &CurveField::<C>::sub(&x1, &x3)),
+            &y1,
+        );
 
-        unimplemented!()
+        Point {
+            coordinates: Some((to_be_bytes(&x3), to_be_bytes(&y3))),
+            curve: PhantomData,
+        }
     }
 }
 
-mod arithmetic_operations {
-    use std::num::Wrapping;
+impl Point<Secp256k1> {
+    /// Scalar multiplication by a secp256k1 scalar: computes kP
+    ///
+    /// Scalar is already reduced mod N, the order of G, so every k in [0, N-1]
+    /// gives a different multiple of G. See mul_u256 for the algorithm.
+    pub fn mul(&self, k: &Scalar) -> Self {
+        self.mul_u256(&U256::from(*k))
+    }
+}
 
-    use helpers::utils::reduce_modulus;
+/// Modular arithmetic on 256-bit integers for any modulus
+///
+/// FieldElement and Scalar cover arithmetic mod P and mod N, these functions
+/// are for other moduli (e.g. a different curve, or small moduli in tests).
+pub mod arithmetic_operations {
+    use helpers::limbs::{self, Limbs, from_be_bytes, to_be_bytes};
+    use helpers::limbs::{N_LIMBS, P_LIMBS};
+    use helpers::safegcd::SafeGcdContext;
+    use helpers::utils::{Inversion, Reduction, div_rem_limbs, reduce_limbs};
 
     use super::*;
 
+    // The functions taking [u8; 32] are the API boundary, they convert to
+    // 64-bit limbs (see helpers::limbs), do the work there and convert back.
+    // FieldElement and Scalar keep their values as limbs and call the *_mod
+    // functions directly so they never pay for the conversion.
+
     // addition operation: c = (a + b) mod P
     //
     // a and b are inputs being added together
     // modulus is my P (finite field size)
     // Result C must be in range of [0, P-1]
+    pub fn addition(a: &[u8; 32], b: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&add_mod(
+            &from_be_bytes(a),
+            &from_be_bytes(b),
+            &from_be_bytes(modulus),
+        ))
+    }
+
+    // substract operation: c = a - b mod P
     //
-    pub fn addition(a: &[u8; 32], b: &[u8; 32], modulus: &[u8; 32], adjustment: bool) -> [u8; 32] {
-        let mut result = [0; 32];
-        let mut carry = 0;
+    // a and b: 256-bit integers as [u8;32] (big endian)
+    // P: is modulus (the secp256k1 prime / fixed of my finite field)
+    // c: must be in the range of [0, P-1]
+    pub fn subtract(a: &[u8; 32], b: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&sub_mod(
+            &from_be_bytes(a),
+            &from_be_bytes(b),
+            &from_be_bytes(modulus),
+        ))
+    }
+
+    /// computes the product of two 256-bit integers and reduces it modulo the modulus
+    ///
+    /// The reduction used is the fastest one available for the modulus,
+    /// see Reduction::default_for
+    pub fn multiplication(a: &[u8; 32], b: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&mul_mod(
+            &from_be_bytes(a),
+            &from_be_bytes(b),
+            &from_be_bytes(modulus),
+            Reduction::default_for(modulus),
+        ))
+    }
 
-        // iterate over 32 byte arrays from MSB (most significant byte to LSB (least significant
-        // byte)
-        for i in (0..32).rev() {
-            // cast values as u16 to catch overflow
-            let temp = a[i] as u16 + b[i] as u16 + carry as u16;
-            // Set the ith value of result as u8 (0-255) value
-            result[i] = temp as u8;
-            // If overflow occured (a+b was larger then 255) carry it over to the next iteration
-            carry = (temp >> 8) as u8;
+    /// Same as multiplication but with a chosen reduction algorithm
+    ///
+    /// Returns UnsupportedModulus if the reduction can't be used with the
+    /// modulus, e.g. Montgomery with a small modulus, see Reduction::supports
+    pub fn multiplication_with(
+        a: &[u8; 32],
+        b: &[u8; 32],
+        modulus: &[u8; 32],
+        reduction: Reduction,
+    ) -> Result<[u8; 32], ArithmeticError> {
+        if !reduction.supports(modulus) {
+            return Err(ArithmeticError::UnsupportedModulus);
         }
+        Ok(to_be_bytes(&mul_mod(
+            &from_be_bytes(a),
+            &from_be_bytes(b),
+            &from_be_bytes(modulus),
+            reduction,
+        )))
+    }
+
+    /// computes a^2 mod P
+    ///
+    /// Same result as multiplication(a, a, ..) but cheaper, see limbs::square_wide
+    pub fn square(a: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        to_be_bytes(&square_mod(
+            &from_be_bytes(a),
+            &from_be_bytes(modulus),
+            Reduction::default_for(modulus),
+        ))
+    }
+
+    /// computes the modular exponentiation: c = base^exponent mod P
+    ///
+    /// base: must already be reduced, in the range [0, modulus-1]
+    /// exponent: any 256-bit integer (big endian)
+    /// modulus: works for both P and N
+    ///
+    /// NOTE: the number of multiplications depends on the bits of the exponent,
+    /// use pow_ct for secret exponents
+    pub fn pow(base: &[u8; 32], exponent: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        let modulus_bytes = modulus;
+        let modulus = from_be_bytes(modulus);
+        to_be_bytes(&pow_mod(
+            &from_be_bytes(base),
+            &from_be_bytes(exponent),
+            &modulus,
+            Reduction::default_for(modulus_bytes),
+        ))
+    }
+
+    /// computes the modular exponentiation: c = base^exponent mod P
+    /// for secret exponents (e.g. private keys or nonces)
+    pub fn pow_ct(base: &[u8; 32], exponent: &[u8; 32], modulus: &[u8; 32]) -> [u8; 32] {
+        let modulus_bytes = modulus;
+        let modulus = from_be_bytes(modulus);
+        to_be_bytes(&pow_mod_ct(
+            &from_be_bytes(base),
+            &from_be_bytes(exponent),
+            &modulus,
+            Reduction::default_for(modulus_bytes),
+        ))
+    }
+
+    /// computes the modular inverse: c = a^-1 mod P, so that (a * c) mod P = 1
+    ///
+    /// a: any 256-bit integer, it is reduced mod modulus first
+    /// modulus: must be odd (true for both P and N)
+    /// Returns DivisionByZero if a is 0 mod modulus, since 0 has no inverse,
+    /// and NotInvertible if a and modulus share a factor (only possible when
+    /// the modulus is not prime)
+    pub fn inverse(a: &[u8; 32], modulus: &[u8; 32]) -> Result<[u8; 32], ArithmeticError> {
+        inverse_mod(&from_be_bytes(a), &from_be_bytes(modulus)).map(|c| to_be_bytes(&c))
+    }
+
+    /// c = (a + b) mod m, a and b must already be reduced
+    pub const fn add_mod(a: &Limbs, b: &Limbs, modulus: &Limbs) -> Limbs {
+        let (sum, carry) = limbs::add(a, b);
 
         // We need to adjust result if our value is > P or >= P
         //
-        // Only subtract P if result isn’t 0 with a carry, which represents 2^256 mod P
-        let is_zero = result.iter().all(|&x| x == 0);
         // carry != 0: a + b >= 2**256
         // result >= modulus: a + b > P
-        if !adjustment
-            && (carry != 0 || is_greater_or_equal(&result, modulus))
-            && !(is_zero && carry == 1)
-        {
-            result = subtract(&result, modulus, modulus, true)
+        //
+        // When a + b is exactly 2**256 the result limbs are all 0 with a carry,
+        // subtracting P (wrapping) still gives the right answer: 2**256 mod P
+        //
+        // Both cases are handled by reduce_once without branching on the values
+        limbs::reduce_once(&sum, modulus, carry)
+    }
+
+    /// c = (a - b) mod m, a and b must already be reduced
+    pub const fn sub_mod(a: &Limbs, b: &Limbs, modulus: &Limbs) -> Limbs {
+        let (difference, borrow) = limbs::sub(a, b);
+
+        // if borrow is not 0, than result is negative
+        // add P to wrap it back around, the carry out cancels the borrow
+        limbs::conditional_add(&difference, modulus, borrow).0
+    }
+
+    /// c = (a * b) mod m, reducing the 512-bit product with the chosen algorithm
+    pub fn mul_mod(a: &Limbs, b: &Limbs, modulus: &Limbs, reduction: Reduction) -> Limbs {
+        metrics::record(Operation::Multiplication);
+        reduce_limbs(&limbs::mul_wide(a, b), modulus, reduction)
+    }
+
+    /// c = a^2 mod m, reducing the 512-bit square with the chosen algorithm
+    pub fn square_mod(a: &Limbs, modulus: &Limbs, reduction: Reduction) -> Limbs {
+        metrics::record(Operation::Squaring);
+        reduce_limbs(&limbs::square_wide(a), modulus, reduction)
+    }
+
+    /// c = base^exponent mod m
+    ///
+    /// Uses left-to-right square-and-multiply: for each bit of the exponent
+    /// (MSB first) square the running result, and multiply by base when the bit is 1.
+    pub fn pow_mod(base: &Limbs, exponent: &Limbs, modulus: &Limbs, reduction: Reduction) -> Limbs {
+        let mut result = [1, 0, 0, 0];
+
+        for i in (0..256).rev() {
+            result = square_mod(&result, modulus, reduction);
+            if limbs::bit(exponent, i) == 1 {
+                result = mul_mod(&result, base, modulus, reduction);
+            }
         }
 
         result
     }
 
-    // substract operation: c = a - b mod P
-    //
-    // a and b: 256-bit integers as [u8;32] (big endian)
-    // P: is modulus (the secp256k1 prime / fixed of my finite field)
-    // c: must be in the range of [0, P-1]
-    //
-    //NOTE: addition has carries, substract has borrowing when a[i] < b[i]
-    // This implies a negative result, so we add P to wrap it around
-    // (back to postive) while ensuring it's < P.
-    pub fn subtract(a: &[u8; 32], b: &[u8; 32], modulus: &[u8; 32], adjustment: bool) -> [u8; 32] {
-        let mut result = [0; 32];
-        let mut borrow = 0;
-
-        // iterate over 32 byte arrays from MSB to LSB
-        for i in (0..32).rev() {
-            // cast values as u16 to catch overflow
-            let mut temp = Wrapping(a[i] as u16) - Wrapping(b[i] as u16) - Wrapping(borrow as u16);
-            // If underflow (e.g., 0 - 1 = 65535), add 256 to get the byte result (0) and borrow 1
-            if temp > Wrapping(255) {
-                temp += Wrapping(256);
-                borrow = 1;
-            } else {
-                borrow = 0;
-            }
-            result[i] = temp.0 as u8;
+    /// c = base^exponent mod m for secret exponents
+    ///
+    /// Uses the Montgomery ladder, which does exactly one multiplication and
+    /// one squaring per exponent bit, and swaps its two registers with a mask
+    /// instead of branching on the bit. The sequence of operations is therefore
+    /// the same for every exponent.
+    pub fn pow_mod_ct(
+        base: &Limbs,
+        exponent: &Limbs,
+        modulus: &Limbs,
+        reduction: Reduction,
+    ) -> Limbs {
+        // Invariant: r1 = r0 * base
+        let mut r0 = [1, 0, 0, 0];
+        let mut r1 = *base;
+
+        for i in (0..256).rev() {
+            let choice = limbs::bit(exponent, i);
+            limbs::conditional_swap(&mut r0, &mut r1, choice);
+            r1 = mul_mod(&r0, &r1, modulus, reduction);
+            r0 = square_mod(&r0, modulus, reduction);
+            limbs::conditional_swap(&mut r0, &mut r1, choice);
         }
 
-        // if borrow is not 0, than result is negative
-        // run the result through addition to add P to it
-        if !adjustment && borrow != 0 {
-            result = addition(&result, modulus, modulus, true);
+        r0
+    }
+
+    /// c = a^-1 mod m for secret values, the modulus must be prime
+    ///
+    /// By Fermat's little theorem a^(m-1) = 1 mod m, so a^(m-2) = a^-1 mod m.
+    /// This is slower than inverse_mod but runs in constant time, since
+    /// pow_mod_ct does the same operations whatever a is.
+    pub fn inverse_mod_ct(
+        a: &Limbs,
+        modulus: &Limbs,
+        reduction: Reduction,
+    ) -> Result<Limbs, ArithmeticError> {
+        metrics::record(Operation::Inversion);
+        if limbs::is_zero(a) {
+            return Err(ArithmeticError::DivisionByZero);
         }
 
-        println!("result of subtraction {:?}", result);
-        result
+        let exponent = limbs::sub(modulus, &[2, 0, 0, 0]).0;
+        Ok(pow_mod_ct(a, &exponent, modulus, reduction))
     }
 
-    /// computes the product of two 256-bit integers and reduces it modulos P
-    /// 1. calculate the product of each byte pair
-    /// 2. Place these products in the correct positions of the result
-    /// 3. Handle carriers properly as they propagate through the array
-    pub fn multiplication(
-        a: &[u8; 32],
-        b: &[u8; 32],
-        modulus: &[u8; 32],
-        adjustment: bool,
-    ) -> [u8; 32] {
-        let mut full_product = [0x00; 64];
-
-        for i in (0..32).rev() {
-            let mut carry = 0;
-            for j in (0..32).rev() {
-                // low byte index
-                let mut index = 63 - i - j;
-                // calculate byte-wise multiplication
-                let mut sum = a[i] as u16 * b[j] as u16 + full_product[index] as u16 + carry as u16;
-                println!("product for a{} * b{} is {}", i, j, sum);
-                full_product[index] = sum & 0xFF;
-                carry = sum >> 8;
-                //  high byte index
-                index = 62 - i - j;
-                sum = full_product[index] + carry;
-                full_product[index] = sum & 0xFF;
-                carry = sum >> 8;
-
-                while carry > 0 && index > 0 {
-                    index = index - 1;
-                    sum = full_product[index] + carry;
-                    full_product[index] = sum & 0xFF;
-                    carry = sum >> 8;
-                }
+    /// c = a^-1 mod m for secret values with safegcd, the modulus must be prime
+    ///
+    /// P and N use their shared precomputed contexts, any other modulus reuses
+    /// the context from the previous call on this thread when it is the same
+    pub fn inverse_mod_safegcd(a: &Limbs, modulus: &Limbs) -> Result<Limbs, ArithmeticError> {
+        metrics::record(Operation::Inversion);
+        if limbs::is_zero(a) {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+
+        Ok(match *modulus {
+            P_LIMBS => SafeGcdContext::for_p().invert_limbs(a),
+            N_LIMBS => SafeGcdContext::for_n().invert_limbs(a),
+            _ => SafeGcdContext::cached(modulus).invert_limbs(a),
+        })
+    }
+
+    /// c = a^-1 mod m for secret values with the chosen algorithm, the modulus must be prime
+    ///
+    /// reduction is only used by the exponentiation
+    pub fn inverse_mod_with(
+        a: &Limbs,
+        modulus: &Limbs,
+        inversion: Inversion,
+        reduction: Reduction,
+    ) -> Result<Limbs, ArithmeticError> {
+        match inversion {
+            Inversion::Exponentiation => inverse_mod_ct(a, modulus, reduction),
+            Inversion::SafeGcd => inverse_mod_safegcd(a, modulus),
+        }
+    }
+
+    /// c = a^-1 mod m
+    ///
+    /// Uses the binary extended Euclidean algorithm, which only needs
+    /// subtraction and halving, both of which are cheap.
+    ///
+    /// NOTE: the number of steps depends on a, use inverse_mod_ct for secret values
+    pub fn inverse_mod(a: &Limbs, modulus: &Limbs) -> Result<Limbs, ArithmeticError> {
+        metrics::record(Operation::Inversion);
+        // reduce first, so a multiple of the modulus is reported as 0 below
+        // (a modulus of 0 is DivisionByZero too)
+        let a = if limbs::is_greater_or_equal(a, modulus) {
+            div_rem_limbs(&[a[0], a[1], a[2], a[3], 0, 0, 0, 0], modulus)?.1
+        } else {
+            *a
+        };
+        if limbs::is_zero(&a) {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+
+        let one = [1, 0, 0, 0];
+
+        // Invariants: x1 * a = u mod P and x2 * a = v mod P
+        // Once u or v reaches 1 its x is the inverse
+        let mut u = a;
+        let mut v = *modulus;
+        let mut x1 = one;
+        let mut x2 = [0; 4];
+
+        while u != one && v != one {
+            while u[0] & 1 == 0 {
+                u = limbs::shift_right_one(&u, 0);
+                x1 = halve(&x1, modulus);
+            }
+            while v[0] & 1 == 0 {
+                v = limbs::shift_right_one(&v, 0);
+                x2 = halve(&x2, modulus);
+            }
+            if limbs::is_greater_or_equal(&u, &v) {
+                u = limbs::sub(&u, &v).0;
+                x1 = sub_mod(&x1, &x2, modulus);
+            } else {
+                v = limbs::sub(&v, &u).0;
+                x2 = sub_mod(&x2, &x1, modulus);
+            }
+            // u and v keep gcd(a, m) as a factor, so when it isn't 1 they end up
+            // equal to it instead of 1 and u becomes 0 (v only ever shrinks to
+            // v - u > 0). Halving 0 would loop forever.
+            if limbs::is_zero(&u) {
+                return Err(ArithmeticError::NotInvertible);
             }
         }
 
-        // Normalize carries
-        let mut carry = 0;
-        let mut normalized_product = [0u8; 64];
-        for k in (0..64).rev() {
-            let sum = full_product[k] + carry;
-            full_product[k] = sum & 0xFF;
-            carry = sum >> 8;
-            normalized_product[k] = full_product[k] as u8;
+        if u == one { Ok(x1) } else { Ok(x2) }
+    }
+
+    /// Inverts every value of the slice in place mod m, using a single inversion
+    ///
+    /// Montgomery's trick: with the running products a, ab, abc the inverse
+    /// of the last one, (abc)^-1, gives every other inverse by multiplying back:
+    /// c^-1 = (abc)^-1 * ab, then (ab)^-1 = (abc)^-1 * c, and so on. That is
+    /// one inversion plus 3(n - 1) multiplications instead of n inversions,
+    /// which matters when e.g. converting many Jacobian points to affine or
+    /// verifying many signatures.
+    ///
+    /// 0 has no inverse, zero values are skipped and stay 0 so they don't
+    /// make the whole product 0. The modulus must be prime (P and N are), so
+    /// that a product of nonzero values is never 0.
+    pub fn batch_invert_limbs(values: &mut [Limbs], modulus: &Limbs, reduction: Reduction) {
+        // products[i] is the product of the nonzero values before i
+        let mut products = Vec::with_capacity(values.len());
+        let mut product = [1, 0, 0, 0];
+        for value in values.iter() {
+            products.push(product);
+            if !limbs::is_zero(value) {
+                product = mul_mod(&product, value, modulus, reduction);
+            }
         }
 
-        // modular reduction
-        // Intermediate result is a [u16; 64], reduce it using mod to [u8; 32]
-        let reduced_product = reduce_modulus(normalized_product, *modulus);
+        // product is never 0, so the inversion can't fail
+        let mut inverse = inverse_mod_with(&product, modulus, Inversion::default(), reduction)
+            .expect("product of nonzero values");
+
+        // walk back, inverse is always the inverse of the product up to and including i
+        for (value, product) in values.iter_mut().zip(products).rev() {
+            if limbs::is_zero(value) {
+                continue;
+            }
+            let value_inverse = mul_mod(&inverse, &product, modulus, reduction);
+            inverse = mul_mod(&inverse, value, modulus, reduction);
+            *value = value_inverse;
+        }
+    }
 
-        println!("full product is; {:?}", full_product);
-        reduced_product
+    // computes a / 2 mod P
+    //
+    // if a is even we can just shift, if a is odd then a + P is even
+    // and (a + P) / 2 is the answer. a + P can be 257 bits long so the
+    // carry is shifted back in as the top bit.
+    fn halve(a: &Limbs, modulus: &Limbs) -> Limbs {
+        let (sum, carry) = limbs::conditional_add(a, modulus, a[0] & 1);
+        limbs::shift_right_one(&sum, carry)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use arithmetic_operations::{addition, multiplication, subtract};
+    use arithmetic_operations::{
+        addition, batch_invert_limbs, inverse, inverse_mod_ct, inverse_mod_with, mul_mod,
+        multiplication, multiplication_with, pow, pow_ct, square, subtract,
+    };
+    use curve::{Toy, small};
+    use helpers::limbs::{from_be_bytes, to_be_bytes};
+    use helpers::utils::{Inversion, Reduction, random_bytes, reduce_modulus};
 
     use super::*;
 
@@ -271,7 +818,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x08,
         ];
-        let result = addition(&a, &b, &P, false);
+        let result = addition(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -292,7 +839,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x01, 0x00,
         ];
-        let result = addition(&a, &b, &P, false);
+        let result = addition(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -304,10 +851,10 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x01,
         ];
-        a = subtract(&a, &one, &P, false); // P - 1
+        a = subtract(&a, &one, &P); // P - 1
         let b = one;
         let correct_result = [0; 32]; // 0 mod P
-        let result = addition(&a, &b, &P, false);
+        let result = addition(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
     #[test]
@@ -318,12 +865,44 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x01,
         ]; // 1
-        let correct_result = [0; 32]; // 0 mod P (since 2^256 - 1 + 1 = 2^256 = 0 mod P)
-        let result = addition(&a, &b, &P, false);
-        println!("2^256 - 1 + 1 = {:?}", result);
+        // 2^256 - 1 + 1 = 2^256 and 2^256 mod P = 2^32 + 977 (0x1000003d1)
+        //
+        // This used to expect 0: addition skipped the reduction when the sum
+        // was exactly 2^256. That is wrong for inputs in range as well, see
+        // test_addition_sum_of_exactly_2_256
+        let correct_result = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x03, 0xd1,
+        ];
+        let result = addition(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
+    #[test]
+    fn test_addition_sum_of_exactly_2_256() {
+        // both inputs are below P and (P - 1) + (2^32 + 978) = 2^256, so the
+        // sum has a carry and all 0 bytes but is not 0 mod P
+        let one = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01,
+        ];
+        let a = subtract(&P, &one, &P); // P - 1
+        let b = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x03, 0xd2,
+        ]; // 2^32 + 978
+        // (P - 1) + (2^32 + 978) = P + 2^32 + 977 = 2^32 + 977 mod P
+        let correct_result = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x03, 0xd1,
+        ];
+        assert_eq!(addition(&a, &b, &P), correct_result);
+    }
+
     #[test]
     fn test_addition_two_large_numbers() {
         let two = [
@@ -341,11 +920,10 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x05,
         ];
-        let a = subtract(&P, &two, &P, false); // P - 2
-        let b = subtract(&P, &three, &P, false); // P - 3
-        let correct_result = subtract(&P, &five, &P, false); // P - 5
-        println!("correct result {:?}", correct_result);
-        let result = addition(&a, &b, &P, false);
+        let a = subtract(&P, &two, &P); // P - 2
+        let b = subtract(&P, &three, &P); // P - 3
+        let correct_result = subtract(&P, &five, &P); // P - 5
+        let result = addition(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -368,7 +946,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0xFF,
         ];
-        let result = subtract(&a, &b, &P, false);
+        let result = subtract(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -392,8 +970,8 @@ mod tests {
         let mut correct_result = P;
         // correct result here is P - 2
         // We'll calculate it dynamically using the subtract function itself
-        correct_result = subtract(&correct_result, &two, &P, false);
-        let result = subtract(&a, &b, &P, false);
+        correct_result = subtract(&correct_result, &two, &P);
+        let result = subtract(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -406,9 +984,9 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x01,
         ];
-        b = subtract(&b, &one, &P, false); // P - 1
+        b = subtract(&b, &one, &P); // P - 1
         let correct_result = one; // 1
-        let result = subtract(&a, &b, &P, false);
+        let result = subtract(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -424,10 +1002,10 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x02,
         ];
-        let a = subtract(&P, &one, &P, false); // P - 1
-        let b = subtract(&P, &two, &P, false); // P - 2
+        let a = subtract(&P, &one, &P); // P - 1
+        let b = subtract(&P, &two, &P); // P - 2
         let correct_result = one; // 1
-        let result = subtract(&a, &b, &P, false);
+        let result = subtract(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
@@ -436,41 +1014,763 @@ mod tests {
         let a = P;
         let b = P;
         let correct_result = [0; 32];
-        let result = subtract(&a, &b, &P, false);
+        let result = subtract(&a, &b, &P);
         assert_eq!(result, correct_result);
     }
 
     // Test Multiplication
 
-    // #[test]
-    // fn test_multiplication_no_carry() {
-    //     let a = [0; 32];
-    //     let b = [0; 32];
-    //     let correct_result = [0; 64];
-    //     let result = multiplication(&a, &b, &P, false);
-    //     assert_eq!(result, correct_result);
-    // }
-    //
-    // #[test]
-    // fn test_multiplication_with_carry() {
-    //     let a = [
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0xFF,
-    //     ];
-    //     let b = [
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0xFF,
-    //     ];
-    //     let correct_result = [
-    //         0xFE, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    //     ];
-    //     let result = multiplication(&a, &b, &P, false);
-    //     assert_eq!(result, correct_result);
-    // }
+    #[test]
+    fn test_multiplication_no_carry() {
+        let a = [0; 32];
+        let b = [0; 32];
+        let correct_result = [0; 32];
+        let result = multiplication(&a, &b, &P);
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_multiplication_with_carry() {
+        let a = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xFF,
+        ];
+        let b = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xFF,
+        ];
+        // 255 * 255 = 65025 = 0xFE01
+        let correct_result = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xFE, 0x01,
+        ];
+        let result = multiplication(&a, &b, &P);
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_multiplication_with_every_reduction() {
+        // (P - 2) * (P - 3) = (-2) * (-3) = 6 mod P, and the same for N
+        let mut six = [0; 32];
+        six[31] = 0x06;
+
+        for modulus in [P, N] {
+            let mut minus_two = modulus;
+            minus_two[31] -= 2;
+            let mut minus_three = modulus;
+            minus_three[31] -= 3;
+
+            let mut reductions = vec![Reduction::Barrett, Reduction::Montgomery];
+            reductions.push(Reduction::LongDivision);
+            if modulus == P {
+                reductions.push(Reduction::SpecialForm);
+            }
+            for reduction in reductions {
+                let result = multiplication_with(&minus_two, &minus_three, &modulus, reduction);
+                assert_eq!(result.unwrap(), six);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiplication_with_other_modulus() {
+        // (p - 2) * (p - 3) = 6 mod P-256's prime, which works with every
+        // reduction but the special form. The second call reuses the context.
+        let modulus = Secp256r1::P;
+        let mut minus_two = modulus;
+        minus_two[31] -= 2;
+        let mut minus_three = modulus;
+        minus_three[31] -= 3;
+        let mut six = [0; 32];
+        six[31] = 0x06;
+        for reduction in [
+            Reduction::Barrett,
+            Reduction::Montgomery,
+            Reduction::LongDivision,
+        ] {
+            for _ in 0..2 {
+                let result = multiplication_with(&minus_two, &minus_three, &modulus, reduction);
+                assert_eq!(result.unwrap(), six);
+            }
+        }
+        assert!(matches!(
+            multiplication_with(&six, &six, &modulus, Reduction::SpecialForm),
+            Err(ArithmeticError::UnsupportedModulus)
+        ));
+    }
+
+    #[test]
+    fn test_multiplication_with_unsupported_modulus() {
+        // Barrett and Montgomery need a full size modulus, an error instead of a panic
+        let modulus = small(23);
+        for reduction in [Reduction::Barrett, Reduction::Montgomery] {
+            assert!(matches!(
+                multiplication_with(&small(3), &small(5), &modulus, reduction),
+                Err(ArithmeticError::UnsupportedModulus)
+            ));
+        }
+        let result = multiplication_with(&small(3), &small(5), &modulus, Reduction::LongDivision);
+        assert_eq!(result.unwrap(), small(15));
+    }
+
+    #[test]
+    fn test_square_matches_multiplication() {
+        let mut state = 0xBB67AE8584CAA73B;
+        for modulus in [P, N] {
+            for _ in 0..100 {
+                let a = reduce_modulus(random_bytes(&mut state), modulus);
+                assert_eq!(square(&a, &modulus), multiplication(&a, &a, &modulus));
+            }
+
+            let mut modulus_minus_one = modulus;
+            modulus_minus_one[31] -= 1;
+            assert_eq!(
+                square(&modulus_minus_one, &modulus),
+                multiplication(&modulus_minus_one, &modulus_minus_one, &modulus)
+            );
+        }
+    }
+
+    // Test Inverse
+
+    #[test]
+    fn test_inverse_small_modulus() {
+        let mut three = [0; 32];
+        three[31] = 0x03;
+        let mut seven = [0; 32];
+        seven[31] = 0x07;
+        // 3 * 5 = 15 = 1 mod 7
+        let mut correct_result = [0; 32];
+        correct_result[31] = 0x05;
+        let result = inverse(&three, &seven).unwrap();
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_inverse_of_two_mod_p() {
+        let mut two = [0; 32];
+        two[31] = 0x02;
+        // (P + 1) / 2
+        let correct_result = [
+            0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0x7f, 0xff, 0xfe, 0x18,
+        ];
+        let result = inverse(&two, &P).unwrap();
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_inverse_of_zero() {
+        assert!(inverse(&[0; 32], &P).is_err());
+    }
+
+    #[test]
+    fn test_inverse_not_coprime() {
+        let mut three = [0; 32];
+        three[31] = 0x03;
+        let mut nine = [0; 32];
+        nine[31] = 0x09;
+        // gcd(3, 9) = 3, no c gives 3 * c = 1 mod 9
+        assert!(matches!(
+            inverse(&three, &nine),
+            Err(ArithmeticError::NotInvertible)
+        ));
+        // 9 = 0 mod 9
+        assert!(matches!(
+            inverse(&nine, &nine),
+            Err(ArithmeticError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_inverse_unreduced_input() {
+        let mut ten = [0; 32];
+        ten[31] = 0x0a;
+        let mut seven = [0; 32];
+        seven[31] = 0x07;
+        // 10 = 3 mod 7 and 3 * 5 = 15 = 1 mod 7
+        let mut correct_result = [0; 32];
+        correct_result[31] = 0x05;
+        assert_eq!(inverse(&ten, &seven).unwrap(), correct_result);
+        // 2^256 - 1 is above P, it is 2^32 + 976 mod P
+        let max = [0xff; 32];
+        let mut one = [0; 32];
+        one[31] = 0x01;
+        assert_eq!(multiplication(&max, &inverse(&max, &P).unwrap(), &P), one);
+    }
+
+    #[test]
+    fn test_inverse_ct_matches_inverse() {
+        let mut state = 0x2545F4914F6CDD1D;
+        for (modulus, reduction) in [(P, Reduction::SpecialForm), (N, Reduction::Barrett)] {
+            let m = from_be_bytes(&modulus);
+            for _ in 0..10 {
+                let a = reduce_modulus(random_bytes(&mut state), modulus);
+                let expected = inverse(&a, &modulus).unwrap();
+                let result = inverse_mod_ct(&from_be_bytes(&a), &m, reduction).unwrap();
+                assert_eq!(to_be_bytes(&result), expected);
+            }
+        }
+        assert!(inverse_mod_ct(&[0; 4], &from_be_bytes(&P), Reduction::SpecialForm).is_err());
+    }
+
+    #[test]
+    fn test_batch_invert_limbs() {
+        // every value mod 23 at once, with 0 in the middle
+        let modulus = [23, 0, 0, 0];
+        let mut values: Vec<_> = (0..23).map(|value| [value, 0, 0, 0]).collect();
+        batch_invert_limbs(&mut values, &modulus, Reduction::LongDivision);
+        assert_eq!(values[0], [0; 4]);
+        for (value, value_inverse) in values.iter().enumerate().skip(1) {
+            let product = mul_mod(
+                &[value as u64, 0, 0, 0],
+                value_inverse,
+                &modulus,
+                Reduction::LongDivision,
+            );
+            assert_eq!(product, [1, 0, 0, 0]);
+        }
+
+        // empty, all zero and single element batches
+        batch_invert_limbs(&mut [], &modulus, Reduction::LongDivision);
+        let mut zeros = [[0; 4]; 3];
+        batch_invert_limbs(&mut zeros, &modulus, Reduction::LongDivision);
+        assert_eq!(zeros, [[0; 4]; 3]);
+        let mut single = [[22, 0, 0, 0]];
+        batch_invert_limbs(&mut single, &modulus, Reduction::LongDivision);
+        assert_eq!(single, [[22, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_inversion_algorithms_agree() {
+        let mut state = 0x1F83D9ABFB41BD6B;
+        for (modulus, reduction) in [(P, Reduction::SpecialForm), (N, Reduction::Barrett)] {
+            let m = from_be_bytes(&modulus);
+            for _ in 0..10 {
+                let a = from_be_bytes(&reduce_modulus(random_bytes(&mut state), modulus));
+                let safegcd = inverse_mod_with(&a, &m, Inversion::SafeGcd, reduction).unwrap();
+                let exponentiation =
+                    inverse_mod_with(&a, &m, Inversion::Exponentiation, reduction).unwrap();
+                assert_eq!(safegcd, exponentiation);
+            }
+            for inversion in [Inversion::SafeGcd, Inversion::Exponentiation] {
+                assert!(inverse_mod_with(&[0; 4], &m, inversion, reduction).is_err());
+            }
+        }
+    }
+
+    // Test Exponentiation
+
+    #[test]
+    fn test_pow_small_exponent() {
+        let mut two = [0; 32];
+        two[31] = 0x02;
+        let mut ten = [0; 32];
+        ten[31] = 0x0a;
+        // 2^10 = 1024
+        let mut correct_result = [0; 32];
+        correct_result[30] = 0x04;
+        let result = pow(&two, &ten, &P);
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_pow_wraps_around_p() {
+        let mut two = [0; 32];
+        two[31] = 0x02;
+        let mut two_fifty_six = [0; 32];
+        two_fifty_six[30] = 0x01;
+        // 2^256 mod P = 2^32 + 977
+        let correct_result = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x03, 0xd1,
+        ];
+        let result = pow(&two, &two_fifty_six, &P);
+        assert_eq!(result, correct_result);
+    }
+
+    #[test]
+    fn test_pow_zero_exponent() {
+        let mut seven = [0; 32];
+        seven[31] = 0x07;
+        let mut one = [0; 32];
+        one[31] = 0x01;
+        assert_eq!(pow(&seven, &[0; 32], &P), one);
+        assert_eq!(pow_ct(&seven, &[0; 32], &P), one);
+    }
+
+    #[test]
+    fn test_pow_fermat_small_prime() {
+        // Fermat's little theorem: a^(p-1) = 1 mod p for prime p
+        let mut thirteen = [0; 32];
+        thirteen[31] = 0x0d;
+        let mut twelve = [0; 32];
+        twelve[31] = 0x0c;
+        let mut one = [0; 32];
+        one[31] = 0x01;
+        for base in 1..13 {
+            let mut a = [0; 32];
+            a[31] = base;
+            assert_eq!(pow(&a, &twelve, &thirteen), one);
+            assert_eq!(pow_ct(&a, &twelve, &thirteen), one);
+        }
+    }
+
+    #[test]
+    fn test_pow_ct_matches_pow() {
+        let mut modulus = [0; 32];
+        modulus[31] = 0xfb; // 251
+        let mut base = [0; 32];
+        base[31] = 0x05;
+        for e in [0, 1, 2, 3, 0x7f, 0x80, 0xa5, 0xff] {
+            let mut exponent = [0; 32];
+            exponent[31] = e;
+            assert_eq!(
+                pow_ct(&base, &exponent, &modulus),
+                pow(&base, &exponent, &modulus)
+            );
+        }
+    }
+
+    // Test Points
+
+    #[test]
+    fn test_generator_on_each_curve() {
+        let g: Point = Point::generator();
+        assert_eq!(g.x(), Some(GENERATOR_X));
+        assert_eq!(g.y(), Some(GENERATOR_Y));
+        assert!(g.is_on_curve());
+
+        let g = Point::<Secp256r1>::generator();
+        assert_eq!(g.x(), Some(Secp256r1::GENERATOR_X));
+        assert!(g.is_on_curve());
+
+        let g = Point::<Toy>::generator();
+        assert_eq!(g.x(), Some(small(47)));
+        assert_eq!(g.y(), Some(small(71)));
+    }
+
+    #[test]
+    fn test_point_new() {
+        // (47, 152) is the other point with x = 47: 152 = -71 mod 223
+        assert!(Point::<Toy>::new(small(47), small(152)).is_ok());
+        assert!(Point::<Toy>::new(small(47), small(72)).is_err());
+        // 71 + 223 is the same point but not reduced
+        let mut unreduced = [0x00; 32];
+        unreduced[30] = 0x01;
+        unreduced[31] = 0x26;
+        assert!(Point::<Toy>::new(small(47), unreduced).is_err());
+
+        assert!(Point::<Secp256k1>::new(GENERATOR_X, GENERATOR_Y).is_ok());
+        // the secp256k1 generator is not on P-256
+        assert!(Point::<Secp256r1>::new(GENERATOR_X, GENERATOR_Y).is_err());
+    }
+
+    #[test]
+    fn test_double_generator() {
+        // the published values of 2G and 4G for secp256k1
+        let two_g_x = [
+            0xc6, 0x04, 0x7f, 0x94, 0x41, 0xed, 0x7d, 0x6d, 0x30, 0x45, 0x40, 0x6e, 0x95, 0xc0,
+            0x7c, 0xd8, 0x5c, 0x77, 0x8e, 0x4b, 0x8c, 0xef, 0x3c, 0xa7, 0xab, 0xac, 0x09, 0xb9,
+            0x5c, 0x70, 0x9e, 0xe5,
+        ];
+        let two_g_y = [
+            0x1a, 0xe1, 0x68, 0xfe, 0xa6, 0x3d, 0xc3, 0x39, 0xa3, 0xc5, 0x84, 0x19, 0x46, 0x6c,
+            0xea, 0xee, 0xf7, 0xf6, 0x32, 0x65, 0x32, 0x66, 0xd0, 0xe1, 0x23, 0x64, 0x31, 0xa9,
+            0x50, 0xcf, 0xe5, 0x2a,
+        ];
+        let g: Point = Point::generator();
+        let two_g = g.double();
+        assert_eq!(two_g, Point::new(two_g_x, two_g_y).unwrap());
+
+        let four_g_x = [
+            0xe4, 0x93, 0xdb, 0xf1, 0xc1, 0x0d, 0x80, 0xf3, 0x58, 0x1e, 0x49, 0x04, 0x93, 0x0b,
+            0x14, 0x04, 0xcc, 0x6c, 0x13, 0x90, 0x0e, 0xe0, 0x75, 0x84, 0x74, 0xfa, 0x94, 0xab,
+            0xe8, 0xc4, 0xcd, 0x13,
+        ];
+        let four_g_y = [
+            0x51, 0xed, 0x99, 0x3e, 0xa0, 0xd4, 0x55, 0xb7, 0x56, 0x42, 0xe2, 0x09, 0x8e, 0xa5,
+            0x14, 0x48, 0xd9, 0x67, 0xae, 0x33, 0xbf, 0xbd, 0xfe, 0x40, 0xcf, 0xe9, 0x7b, 0xdc,
+            0x47, 0x73, 0x99, 0x22,
+        ];
+        assert_eq!(two_g.double(), Point::new(four_g_x, four_g_y).unwrap());
+    }
+
+    #[test]
+    fn test_double_generator_p256() {
+        // a = -3 on P-256, so this checks a is used in the slope
+        let two_g_x = [
+            0x7c, 0xf2, 0x7b, 0x18, 0x8d, 0x03, 0x4f, 0x7e, 0x8a, 0x52, 0x38, 0x03, 0x04, 0xb5,
+            0x1a, 0xc3, 0xc0, 0x89, 0x69, 0xe2, 0x77, 0xf2, 0x1b, 0x35, 0xa6, 0x0b, 0x48, 0xfc,
+            0x47, 0x66, 0x99, 0x78,
+        ];
+        let two_g_y = [
+            0x07, 0x77, 0x55, 0x10, 0xdb, 0x8e, 0xd0, 0x40, 0x29, 0x3d, 0x9a, 0xc6, 0x9f, 0x74,
+            0x30, 0xdb, 0xba, 0x7d, 0xad, 0xe6, 0x3c, 0xe9, 0x82, 0x29, 0x9e, 0x04, 0xb7, 0x9d,
+            0x22, 0x78, 0x73, 0xd1,
+        ];
+        let two_g = Point::<Secp256r1>::generator().double();
+        assert_eq!(two_g, Point::new(two_g_x, two_g_y).unwrap());
+    }
+
+    #[test]
+    fn test_double_small_curve() {
+        // 2 * (47, 71) = (36, 111) on y² = x³ + 7 mod 223
+        let g = Point::<Toy>::generator();
+        assert_eq!(g.double(), Point::new(small(36), small(111)).unwrap());
+    }
+
+    #[test]
+    fn test_double_vertical_tangent() {
+        // 6³ + 7 = 223 = 0 mod 223, so (6, 0) is on the curve and 2 * (6, 0) = O
+        let point = Point::<Toy>::new(small(6), small(0)).unwrap();
+        assert_eq!(point.double(), Point::identity());
+    }
+
+    #[test]
+    fn test_double_identity() {
+        let identity: Point = Point::identity();
+        assert_eq!(identity.double(), Point::identity());
+        assert!(identity.is_on_curve());
+        assert_eq!(identity.x(), None);
+    }
+
+    #[test]
+    fn test_add_generator_and_double() {
+        // the published value of 3G for secp256k1
+        let three_g_x = [
+            0xf9, 0x30, 0x8a, 0x01, 0x92, 0x58, 0xc3, 0x10, 0x49, 0x34, 0x4f, 0x85, 0xf8, 0x9d,
+            0x52, 0x29, 0xb5, 0x31, 0xc8, 0x45, 0x83, 0x6f, 0x99, 0xb0, 0x86, 0x01, 0xf1, 0x13,
+            0xbc, 0xe0, 0x36, 0xf9,
+        ];
+        let three_g_y = [
+            0x38, 0x8f, 0x7b, 0x0f, 0x63, 0x2d, 0xe8, 0x14, 0x0f, 0xe3, 0x37, 0xe6, 0x2a, 0x37,
+            0xf3, 0x56, 0x65, 0x00, 0xa9, 0x99, 0x34, 0xc2, 0x23, 0x1b, 0x6c, 0xb9, 0xfd, 0x75,
+            0x84, 0xb8, 0xe6, 0x72,
+        ];
+        let g: Point = Point::generator();
+        let three_g = Point::add(g.clone(), g.double()).unwrap();
+        assert_eq!(three_g, Point::new(three_g_x, three_g_y).unwrap());
+        // addition is commutative
+        assert_eq!(Point::add(g.double(), g.clone()).unwrap(), three_g);
+    }
+
+    #[test]
+    fn test_add_equal_points_doubles() {
+        let g: Point = Point::generator();
+        assert_eq!(Point::add(g.clone(), g.clone()).unwrap(), g.double());
+
+        let g = Point::<Toy>::generator();
+        assert_eq!(Point::add(g.clone(), g.clone()).unwrap(), g.double());
+    }
+
+    #[test]
+    fn test_add_identity() {
+        let g: Point = Point::generator();
+        assert_eq!(Point::add(g.clone(), Point::identity()).unwrap(), g);
+        assert_eq!(Point::add(Point::identity(), g.clone()).unwrap(), g);
+        assert_eq!(
+            Point::add(Point::<Toy>::identity(), Point::identity()).unwrap(),
+            Point::identity()
+        );
+    }
+
+    #[test]
+    fn test_add_inverse_points() {
+        // -(47, 71) = (47, 223 - 71) = (47, 152)
+        let g = Point::<Toy>::generator();
+        let minus_g = Point::new(small(47), small(152)).unwrap();
+        assert_eq!(Point::add(g, minus_g).unwrap(), Point::identity());
+
+        // -(6, 0) = (6, 0), so adding it to itself gives the identity as well
+        let point = Point::<Toy>::new(small(6), small(0)).unwrap();
+        assert_eq!(Point::add(point.clone(), point).unwrap(), Point::identity());
+    }
+
+    #[test]
+    fn test_add_small_curve_multiples() {
+        // the multiples of (47, 71) on y² = x³ + 7 mod 223, G has order 21
+        let multiples = [
+            (47, 71),
+            (36, 111),
+            (15, 137),
+            (194, 51),
+            (126, 96),
+            (139, 137),
+            (92, 47),
+            (116, 55),
+            (69, 86),
+            (154, 150),
+            (154, 73),
+            (69, 137),
+            (116, 168),
+            (92, 176),
+            (139, 86),
+            (126, 127),
+            (194, 172),
+            (15, 86),
+            (36, 112),
+            (47, 152),
+        ];
+        let g = Point::<Toy>::generator();
+        let mut sum = Point::identity();
+        for (x, y) in multiples {
+            sum = Point::add(sum, g.clone()).unwrap();
+            assert_eq!(sum, Point::new(small(x), small(y)).unwrap());
+        }
+        // 21G = O
+        assert_eq!(Point::add(sum, g).unwrap(), Point::identity());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_add_point_not_on_curve() {
+        // Point::new rejects this, build it directly
+        let off_curve = Point::<Toy> {
+            coordinates: Some((small(47), small(72))),
+            curve: PhantomData,
+        };
+        let g = Point::<Toy>::generator();
+        let _ = Point::add(g, off_curve);
+    }
+
+    #[test]
+    fn test_identity() {
+        let identity: Point = Point::identity();
+        assert!(identity.is_identity());
+        assert!(!Point::<Secp256k1>::generator().is_identity());
+        assert_eq!(identity.negate(), identity);
+        assert_eq!(identity.to_sec1(true), vec![0x00]);
+        assert_eq!(identity.to_sec1(false), vec![0x00]);
+    }
+
+    #[test]
+    fn test_negate() {
+        let g = Point::<Toy>::generator();
+        let minus_g = g.negate();
+        assert_eq!(minus_g, Point::new(small(47), small(152)).unwrap());
+        assert_eq!(minus_g.negate(), g);
+        assert!(Point::add(g, minus_g).unwrap().is_identity());
+
+        // y = 0 is its own negation
+        let point = Point::<Toy>::new(small(6), small(0)).unwrap();
+        assert_eq!(point.negate(), point);
+
+        let g: Point = Point::generator();
+        assert!(Point::add(g.clone(), g.negate()).unwrap().is_identity());
+        assert!(g.negate().is_on_curve());
+    }
+
+    #[test]
+    fn test_to_sec1() {
+        let g: Point = Point::generator();
+
+        let compressed = g.to_sec1(true);
+        assert_eq!(compressed.len(), 33);
+        // the y of G ends in 0xb8, so it is even
+        assert_eq!(compressed[0], 0x02);
+        assert_eq!(compressed[1..], GENERATOR_X);
+
+        let uncompressed = g.to_sec1(false);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 0x04);
+        assert_eq!(uncompressed[1..33], GENERATOR_X);
+        assert_eq!(uncompressed[33..], GENERATOR_Y);
+
+        // -G has the odd y
+        assert_eq!(g.negate().to_sec1(true)[0], 0x03);
+
+        // 71 is odd
+        let encoded = Point::<Toy>::generator().to_sec1(true);
+        assert_eq!(encoded[0], 0x03);
+        assert_eq!(encoded[1..], small(47));
+    }
+
+    fn point_from_hex<C: CurveParams>(x: &str, y: &str) -> Point<C> {
+        Point::new(
+            x.parse::<U256>().unwrap().to_be_bytes(),
+            y.parse::<U256>().unwrap().to_be_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mul_small_multiples() {
+        // 1G to 20G for secp256k1
+        let multiples = [
+            (
+                "0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                "0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+            ),
+            (
+                "0xc6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+                "0x1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a",
+            ),
+            (
+                "0xf9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+                "0x388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672",
+            ),
+            (
+                "0xe493dbf1c10d80f3581e4904930b1404cc6c13900ee0758474fa94abe8c4cd13",
+                "0x51ed993ea0d455b75642e2098ea51448d967ae33bfbdfe40cfe97bdc47739922",
+            ),
+            (
+                "0x2f8bde4d1a07209355b4a7250a5c5128e88b84bddc619ab7cba8d569b240efe4",
+                "0xd8ac222636e5e3d6d4dba9dda6c9c426f788271bab0d6840dca87d3aa6ac62d6",
+            ),
+            (
+                "0xfff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a1460297556",
+                "0xae12777aacfbb620f3be96017f45c560de80f0f6518fe4a03c870c36b075f297",
+            ),
+            (
+                "0x5cbdf0646e5db4eaa398f365f2ea7a0e3d419b7e0330e39ce92bddedcac4f9bc",
+                "0x6aebca40ba255960a3178d6d861a54dba813d0b813fde7b5a5082628087264da",
+            ),
+            (
+                "0x2f01e5e15cca351daff3843fb70f3c2f0a1bdd05e5af888a67784ef3e10a2a01",
+                "0x5c4da8a741539949293d082a132d13b4c2e213d6ba5b7617b5da2cb76cbde904",
+            ),
+            (
+                "0xacd484e2f0c7f65309ad178a9f559abde09796974c57e714c35f110dfc27ccbe",
+                "0xcc338921b0a7d9fd64380971763b61e9add888a4375f8e0f05cc262ac64f9c37",
+            ),
+            (
+                "0xa0434d9e47f3c86235477c7b1ae6ae5d3442d49b1943c2b752a68e2a47e247c7",
+                "0x893aba425419bc27a3b6c7e693a24c696f794c2ed877a1593cbee53b037368d7",
+            ),
+            (
+                "0x774ae7f858a9411e5ef4246b70c65aac5649980be5c17891bbec17895da008cb",
+                "0xd984a032eb6b5e190243dd56d7b7b365372db1e2dff9d6a8301d74c9c953c61b",
+            ),
+            (
+                "0xd01115d548e7561b15c38f004d734633687cf4419620095bc5b0f47070afe85a",
+                "0xa9f34ffdc815e0d7a8b64537e17bd81579238c5dd9a86d526b051b13f4062327",
+            ),
+            (
+                "0xf28773c2d975288bc7d1d205c3748651b075fbc6610e58cddeeddf8f19405aa8",
+                "0x0ab0902e8d880a89758212eb65cdaf473a1a06da521fa91f29b5cb52db03ed81",
+            ),
+            (
+                "0x499fdf9e895e719cfd64e67f07d38e3226aa7b63678949e6e49b241a60e823e4",
+                "0xcac2f6c4b54e855190f044e4a7b3d464464279c27a3f95bcc65f40d403a13f5b",
+            ),
+            (
+                "0xd7924d4f7d43ea965a465ae3095ff41131e5946f3c85f79e44adbcf8e27e080e",
+                "0x581e2872a86c72a683842ec228cc6defea40af2bd896d3a5c504dc9ff6a26b58",
+            ),
+            (
+                "0xe60fce93b59e9ec53011aabc21c23e97b2a31369b87a5ae9c44ee89e2a6dec0a",
+                "0xf7e3507399e595929db99f34f57937101296891e44d23f0be1f32cce69616821",
+            ),
+            (
+                "0xdefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34",
+                "0x4211ab0694635168e997b0ead2a93daeced1f4a04a95c0f6cfb199f69e56eb77",
+            ),
+            (
+                "0x5601570cb47f238d2b0286db4a990fa0f3ba28d1a319f5e7cf55c2a2444da7cc",
+                "0xc136c1dc0cbeb930e9e298043589351d81d8e0bc736ae2a1f5192e5e8b061d58",
+            ),
+            (
+                "0x2b4ea0a797a443d293ef5cff444f4979f06acfebd7e86d277475656138385b6c",
+                "0x85e89bc037945d93b343083b5a1c86131a01f60c50269763b570c854e5c09b7a",
+            ),
+            (
+                "0x4ce119c96e2fa357200b559b2f7dd5a5f02d5290aff74b03f3e471b273211c97",
+                "0x12ba26dcb10ec1625da61fa10a844c676162948271d96967450288ee9233dc3a",
+            ),
+        ];
+        let g: Point = Point::generator();
+        for (k, (x, y)) in (1..=20).zip(multiples) {
+            assert_eq!(g.mul(&Scalar::from(k)), point_from_hex(x, y), "{k}G");
+        }
+    }
+
+    #[test]
+    fn test_mul_large_scalars() {
+        let g: Point = Point::generator();
+
+        let k: Scalar = "112233445566778899".parse().unwrap();
+        let expected = point_from_hex(
+            "0xa90cc3d3f3e146daadfc74ca1372207cb4b725ae708cef713a98edd73d99ef29",
+            "0x5a79d6b289610c68bc3b47f3d72f9788a26a06868b4d8e433e1e2ad76fb7dc76",
+        );
+        assert_eq!(g.mul(&k), expected);
+
+        // (N - 1)G = -G
+        let minus_one = -Scalar::ONE;
+        assert_eq!(g.mul(&minus_one), g.negate());
+        // NG = O, so (N - 1)G + G = O
+        assert!(
+            Point::add(g.mul(&minus_one), g.clone())
+                .unwrap()
+                .is_identity()
+        );
+        assert!(g.mul_u256(&U256::from_be_bytes(N)).is_identity());
+    }
+
+    #[test]
+    fn test_mul_p256() {
+        // a published P-256 point multiplication test vector
+        let g = Point::<Secp256r1>::generator();
+        let k: U256 = "112233445566778899".parse().unwrap();
+        let expected = point_from_hex(
+            "0x339150844ec15234807fe862a86be77977dbfb3ae3d96f4c22795513aeaab82f",
+            "0xb1c14ddfdc8ec1b2583f51e85a5eb3a155840f2034730e9b5ada38b674336a21",
+        );
+        assert_eq!(g.mul_u256(&k), expected);
+
+        // the order of G on P-256 is its own n, not secp256k1's N
+        let n = U256::from_be_bytes(Secp256r1::N);
+        assert!(g.mul_u256(&n).is_identity());
+        let n_minus_one = n.wrapping_sub(&U256::ONE);
+        assert_eq!(g.mul_u256(&n_minus_one), g.negate());
+        assert!(!g.mul_u256(&U256::from_be_bytes(N)).is_identity());
+    }
+
+    #[test]
+    fn test_mul_zero_and_identity() {
+        let g: Point = Point::generator();
+        assert!(g.mul(&Scalar::ZERO).is_identity());
+        assert_eq!(g.mul(&Scalar::ONE), g);
+        assert!(
+            Point::<Secp256k1>::identity()
+                .mul(&Scalar::from(7))
+                .is_identity()
+        );
+    }
+
+    #[test]
+    fn test_mul_small_curve() {
+        // G has order 21 on the small curve, so kG repeats every 21 steps
+        let g = Point::<Toy>::generator();
+        let mut sum = Point::identity();
+        for k in 0..45 {
+            assert_eq!(g.mul_u256(&U256::from(k)), sum, "{k}G");
+            sum = Point::add(sum, g.clone()).unwrap();
+        }
+        assert!(g.mul_u256(&U256::from(21)).is_identity());
+        assert_eq!(g.mul_u256(&U256::from(22)), g);
+    }
+
+    #[test]
+    fn test_mul_matches_jacobian() {
+        let g: Point = Point::generator();
+        let k: Scalar = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            .parse()
+            .unwrap();
+        let mut expected = JacobianPoint::identity();
+        for i in 0..256 {
+            expected = expected.double();
+            if i % 2 == 0 {
+                expected = expected.add_affine(&g);
+            }
+        }
+        assert_eq!(g.mul(&k), expected.to_affine());
+    }
 }
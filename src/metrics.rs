@@ -0,0 +1,214 @@
+// Operation counts for comparing algorithms
+//
+// Timings are noisy and depend on the machine, the number of operations an
+// algorithm does is not. With the metrics cargo feature enabled every modular
+// multiplication, squaring, reduction and inversion and every point operation
+// adds one to a counter. Wrapping a call in measure returns what it did, e.g.
+// measure(|| point.mul(&k)).1.point_doublings
+//
+// The counters are per thread, so tests running in parallel don't see each
+// other's operations. Without the feature nothing is counted, record compiles
+// to nothing and every snapshot is all zeros.
+
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+
+/// The number of operations done on this thread, see snapshot and measure
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Modular multiplications (mod P, N or any other modulus)
+    pub multiplications: u64,
+    /// Modular squarings, counted separately since they are cheaper
+    pub squarings: u64,
+    /// Reductions of a 512-bit product, one per multiplication or squaring
+    /// plus any done directly (e.g. Scalar::from_wide_bytes)
+    pub reductions: u64,
+    /// Modular inversions, whatever the algorithm
+    pub inversions: u64,
+    /// Additions of two different points
+    pub point_additions: u64,
+    /// Point doublings
+    pub point_doublings: u64,
+}
+
+impl Metrics {
+    /// No operations
+    pub const ZERO: Metrics = Metrics {
+        multiplications: 0,
+        squarings: 0,
+        reductions: 0,
+        inversions: 0,
+        point_additions: 0,
+        point_doublings: 0,
+    };
+
+    // the operations done since an earlier snapshot
+    //
+    // A reset in between can make a counter smaller than it was, that count
+    // is lost and stops at 0 instead of overflowing.
+    fn since(&self, earlier: &Metrics) -> Metrics {
+        Metrics {
+            multiplications: self.multiplications.saturating_sub(earlier.multiplications),
+            squarings: self.squarings.saturating_sub(earlier.squarings),
+            reductions: self.reductions.saturating_sub(earlier.reductions),
+            inversions: self.inversions.saturating_sub(earlier.inversions),
+            point_additions: self.point_additions.saturating_sub(earlier.point_additions),
+            point_doublings: self.point_doublings.saturating_sub(earlier.point_doublings),
+        }
+    }
+}
+
+// The operations that are counted, one per field of Metrics
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Operation {
+    Multiplication,
+    Squaring,
+    Reduction,
+    Inversion,
+    PointAddition,
+    PointDoubling,
+}
+
+#[cfg(feature = "metrics")]
+thread_local! {
+    static COUNTS: Cell<Metrics> = const { Cell::new(Metrics::ZERO) };
+}
+
+/// Adds one to the counter for the operation
+#[cfg(feature = "metrics")]
+pub(crate) fn record(operation: Operation) {
+    COUNTS.with(|counts| {
+        let mut current = counts.get();
+        let counter = match operation {
+            Operation::Multiplication => &mut current.multiplications,
+            Operation::Squaring => &mut current.squarings,
+            Operation::Reduction => &mut current.reductions,
+            Operation::Inversion => &mut current.inversions,
+            Operation::PointAddition => &mut current.point_additions,
+            Operation::PointDoubling => &mut current.point_doublings,
+        };
+        *counter += 1;
+        counts.set(current);
+    });
+}
+
+/// Does nothing, the metrics feature is off
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn record(_operation: Operation) {}
+
+/// The operations counted on this thread since it started (or since reset)
+pub fn snapshot() -> Metrics {
+    #[cfg(feature = "metrics")]
+    return COUNTS.with(|counts| counts.get());
+
+    #[cfg(not(feature = "metrics"))]
+    Metrics::ZERO
+}
+
+/// Sets all the counters on this thread back to zero
+pub fn reset() {
+    #[cfg(feature = "metrics")]
+    COUNTS.with(|counts| counts.set(Metrics::ZERO));
+}
+
+/// Runs f and returns its result with the operations it did
+///
+/// Takes a snapshot before and after, so calls can be nested and nothing
+/// needs to be reset first. If f calls reset the counts are not reliable,
+/// they can only be lower than what f did.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Metrics) {
+    let before = snapshot();
+    let result = f();
+    (result, snapshot().since(&before))
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, JacobianPoint, Point, Scalar};
+
+    #[test]
+    fn test_measure_counts_field_operations() {
+        let a = FieldElement::from(3);
+        let b = FieldElement::from(5);
+
+        let (_, counts) = measure(|| a * b);
+        assert_eq!(counts.multiplications, 1);
+        assert_eq!(counts.reductions, 1);
+        assert_eq!(counts.squarings, 0);
+
+        let (_, counts) = measure(|| a.square());
+        assert_eq!(counts.squarings, 1);
+        assert_eq!(counts.multiplications, 0);
+
+        // 256 squarings, plus a multiplication for each 1 bit of the exponent
+        let (_, counts) = measure(|| a.pow(&[0xFF; 32]));
+        assert_eq!(counts.squarings, 256);
+        assert_eq!(counts.multiplications, 256);
+        assert_eq!(counts.reductions, 512);
+
+        let (_, counts) = measure(|| Scalar::from(7).invert());
+        assert_eq!(counts.inversions, 1);
+
+        let (_, counts) = measure(|| Scalar::from_wide_bytes([0xFF; 64]));
+        assert_eq!(counts.reductions, 1);
+    }
+
+    #[test]
+    fn test_measure_counts_point_operations() {
+        let g: Point = Point::generator();
+        let (_, counts) = measure(|| Point::add(g.clone(), g.double()));
+        assert_eq!(counts.point_doublings, 1);
+        assert_eq!(counts.point_additions, 1);
+        // affine formulas invert once per operation
+        assert_eq!(counts.inversions, 2);
+
+        // 20 = 0b10100: 4 doublings and an addition for the second 1 bit
+        let (_, counts) = measure(|| g.mul(&Scalar::from(20)));
+        assert_eq!(counts.point_doublings, 4);
+        assert_eq!(counts.point_additions, 1);
+
+        // Jacobian coordinates only invert once, in to_affine
+        let (_, counts) = measure(|| {
+            let two_g = JacobianPoint::from(&g).double();
+            two_g.add_affine(&g).double().to_affine()
+        });
+        assert_eq!(counts.point_doublings, 2);
+        assert_eq!(counts.point_additions, 1);
+        assert_eq!(counts.inversions, 1);
+    }
+
+    #[test]
+    fn test_measure_nested() {
+        let a = FieldElement::from(3);
+        let (inner, outer) = measure(|| {
+            let _ = a * a;
+            measure(|| a * a).1
+        });
+        assert_eq!(inner.multiplications, 1);
+        assert_eq!(outer.multiplications, 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let _ = FieldElement::from(3) * FieldElement::from(5);
+        assert!(snapshot().multiplications > 0);
+        reset();
+        assert_eq!(snapshot(), Metrics::ZERO);
+    }
+
+    #[test]
+    fn test_reset_inside_measure() {
+        let a = FieldElement::from(3);
+        let _ = a * a * a;
+        // fewer operations after the reset than before it, so the counters
+        // end up lower than the first snapshot
+        let (_, counts) = measure(|| {
+            reset();
+            a * a
+        });
+        assert_eq!(counts.multiplications, 0);
+        assert_eq!(snapshot().multiplications, 1);
+    }
+}
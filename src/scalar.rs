@@ -0,0 +1,394 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::arithmetic_operations::{
+    add_mod, batch_invert_limbs, inverse_mod_with, mul_mod, square_mod, sub_mod,
+};
+use crate::helpers::limbs::{self, Limbs, N_LIMBS, from_be_bytes, to_be_bytes, wide_from_be_bytes};
+use crate::helpers::utils::{Inversion, Reduction, reduce_limbs};
+use crate::{ArithmeticError, ParseError, U256};
+
+// N has no special form, Barrett works for any modulus
+const REDUCTION: Reduction = Reduction::Barrett;
+
+/// An integer mod N, the order of the secp256k1 group
+///
+/// Private keys, nonces (k), message hashes (z) and the signature values
+/// r and s all live mod N, while point coordinates live mod P (see FieldElement).
+/// Keeping them as different types means the two can't be mixed by accident.
+///
+/// The value is stored as four 64-bit limbs (see helpers::limbs) and is always in the range [0, N-1].
+#[derive(Clone, Copy)]
+pub struct Scalar(Limbs);
+
+impl Scalar {
+    /// The additive identity
+    pub const ZERO: Self = Scalar([0, 0, 0, 0]);
+
+    /// The multiplicative identity
+    pub const ONE: Self = Scalar([1, 0, 0, 0]);
+
+    /// Creates a scalar from a 256-bit big endian integer
+    ///
+    /// Returns None if the integer is >= N instead of silently reducing it,
+    /// since a private key or signature value out of range is an error.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let value = from_be_bytes(&bytes);
+        if limbs::is_greater_or_equal(&value, &N_LIMBS) {
+            None
+        } else {
+            Some(Scalar(value))
+        }
+    }
+
+    /// Creates a scalar from a 256-bit big endian integer, reducing it mod N
+    ///
+    /// Use this for values that are allowed to be out of range, such as a
+    /// message hash z. N is larger than 2^255, so a single subtraction is enough.
+    ///
+    /// NOTE: 2^256 - N is about 2^128, so values just below 2^256 map onto
+    /// small scalars a little more often. Use from_wide_bytes when the result
+    /// must be uniform.
+    pub const fn from_bytes_reduced(bytes: [u8; 32]) -> Self {
+        Scalar(limbs::reduce_once(&from_be_bytes(&bytes), &N_LIMBS, 0))
+    }
+
+    /// Creates a scalar from a 512-bit big endian integer, reducing it mod N
+    ///
+    /// Reducing 512 uniformly random bits (e.g. a SHA-512 output, or two
+    /// SHA-256 outputs one after the other) gives a scalar whose bias is
+    /// around 2^-256, so this is the way to hash to a scalar.
+    pub fn from_wide_bytes(bytes: [u8; 64]) -> Self {
+        Scalar(reduce_limbs(
+            &wide_from_be_bytes(&bytes),
+            &N_LIMBS,
+            Reduction::Barrett,
+        ))
+    }
+
+    /// Returns the value as a 256-bit big endian integer in the range [0, N-1]
+    pub const fn to_bytes(&self) -> [u8; 32] {
+        to_be_bytes(&self.0)
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        limbs::is_zero(&self.0)
+    }
+
+    /// Checks the scalar is in the range [1, N-1]
+    ///
+    /// Private keys, nonces and the signature values r and s must all be
+    /// in this range, 0 is not allowed.
+    pub const fn is_valid(&self) -> bool {
+        !self.is_zero()
+    }
+
+    /// Returns self * self, faster than multiplying with itself
+    pub fn square(&self) -> Scalar {
+        Scalar(square_mod(&self.0, &N_LIMBS, REDUCTION))
+    }
+
+    /// Returns the multiplicative inverse a^-1, so that a * a^-1 = 1 mod N
+    ///
+    /// Needed for signing, s = k^-1 * (z + r * d) mod N
+    ///
+    /// Returns DivisionByZero for 0
+    ///
+    /// Runs in constant time, using the default Inversion (safegcd)
+    pub fn invert(&self) -> Result<Scalar, ArithmeticError> {
+        self.invert_with(Inversion::default())
+    }
+
+    /// Same as invert, with a chosen algorithm (see Inversion)
+    pub fn invert_with(&self, inversion: Inversion) -> Result<Scalar, ArithmeticError> {
+        inverse_mod_with(&self.0, &N_LIMBS, inversion, REDUCTION).map(Scalar)
+    }
+
+    /// Inverts every element of the slice in place, using a single inversion
+    ///
+    /// Zero elements stay 0, see arithmetic_operations::batch_invert_limbs
+    pub fn batch_invert(elements: &mut [Scalar]) {
+        let mut values: Vec<Limbs> = elements.iter().map(|element| element.0).collect();
+        batch_invert_limbs(&mut values, &N_LIMBS, REDUCTION);
+        for (element, value) in elements.iter_mut().zip(values) {
+            element.0 = value;
+        }
+    }
+}
+
+// Compared in constant time, see limbs::ct_eq
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Scalar) -> bool {
+        limbs::ct_eq(&self.0, &other.0) == 1
+    }
+}
+
+impl Eq for Scalar {}
+
+/// Parses a decimal or 0x hex value in [0, N-1], see U256::parse_below
+impl FromStr for Scalar {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        U256::parse_below(s, &N_LIMBS).map(Scalar)
+    }
+}
+
+/// Formats the value in decimal
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&U256::from_limbs(self.0), f)
+    }
+}
+
+/// Formats the value in lowercase hex, {:#x} adds the 0x prefix
+impl fmt::LowerHex for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&U256::from_limbs(self.0), f)
+    }
+}
+
+// All 64 hex digits, e.g. Scalar(0x00..01)
+impl fmt::Debug for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scalar({:#066x})", self)
+    }
+}
+
+/// The value as an integer in [0, N-1], e.g. to multiply a point with it
+impl From<Scalar> for U256 {
+    fn from(value: Scalar) -> Self {
+        U256::from_limbs(value.0)
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(value: u64) -> Self {
+        Scalar([value, 0, 0, 0])
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, rhs: Scalar) -> Scalar {
+        Scalar(add_mod(&self.0, &rhs.0, &N_LIMBS))
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, rhs: Scalar) -> Scalar {
+        Scalar(sub_mod(&self.0, &rhs.0, &N_LIMBS))
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, rhs: Scalar) -> Scalar {
+        Scalar(mul_mod(&self.0, &rhs.0, &N_LIMBS, REDUCTION))
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    // -a mod N = (0 - a) mod N, which keeps -0 = 0
+    fn neg(self) -> Scalar {
+        Scalar::ZERO - self
+    }
+}
+
+impl AddAssign for Scalar {
+    fn add_assign(&mut self, rhs: Scalar) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Scalar {
+    fn sub_assign(&mut self, rhs: Scalar) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Scalar {
+    fn mul_assign(&mut self, rhs: Scalar) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::N;
+    use crate::helpers::utils::{random_bytes, reduce_modulus};
+
+    #[test]
+    fn test_from_bytes_rejects_out_of_range() {
+        assert_eq!(Scalar::from_bytes(N), None);
+        assert_eq!(Scalar::from_bytes([0xFF; 32]), None);
+
+        let mut n_minus_one = N;
+        n_minus_one[31] -= 1;
+        let scalar = Scalar::from_bytes(n_minus_one).unwrap();
+        assert_eq!(scalar, -Scalar::ONE);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!Scalar::ZERO.is_valid());
+        assert!(Scalar::ONE.is_valid());
+        assert!((-Scalar::ONE).is_valid());
+    }
+
+    #[test]
+    fn test_scalar_addition_wraps_around_n() {
+        let n_minus_one = -Scalar::ONE;
+        assert_eq!(n_minus_one + Scalar::ONE, Scalar::ZERO);
+        assert_eq!(n_minus_one + Scalar::from(5), Scalar::from(4));
+    }
+
+    #[test]
+    fn test_scalar_subtraction_wraps_around_n() {
+        let mut n_minus_two = N;
+        n_minus_two[31] -= 2;
+        let result = Scalar::from(2) - Scalar::from(4);
+        assert_eq!(result.to_bytes(), n_minus_two);
+    }
+
+    #[test]
+    fn test_scalar_multiplication() {
+        let a = Scalar::from(0xFFFF);
+        let b = Scalar::from(0x10001);
+        assert_eq!(a * b, Scalar::from(0xFFFFFFFF));
+    }
+
+    #[test]
+    fn test_scalar_multiplication_full_size() {
+        // (N - 1) * (N - 1) = (-1) * (-1) = 1
+        let n_minus_one = -Scalar::ONE;
+        assert_eq!(n_minus_one * n_minus_one, Scalar::ONE);
+
+        // (N - 1) * 2 = -2
+        let two = Scalar::from(2);
+        assert_eq!(n_minus_one * two, -two);
+    }
+
+    #[test]
+    fn test_scalar_square() {
+        let a = Scalar::from(0xFFFF);
+        assert_eq!(a.square(), Scalar::from(0xFFFE0001));
+
+        let n_minus_one = -Scalar::ONE;
+        assert_eq!(n_minus_one.square(), Scalar::ONE);
+    }
+
+    #[test]
+    fn test_scalar_negation() {
+        let a = Scalar::from(7);
+        assert_eq!(a + -a, Scalar::ZERO);
+        assert_eq!(-Scalar::ZERO, Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_scalar_inversion() {
+        for value in [1, 2, 3, 7, 255] {
+            let a = Scalar::from(value);
+            let a_inv = a.invert().unwrap();
+            assert_eq!(a * a_inv, Scalar::ONE);
+            assert_eq!(a_inv.invert().unwrap(), a);
+        }
+        assert!(matches!(
+            Scalar::ZERO.invert(),
+            Err(ArithmeticError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_scalar_assign_operations() {
+        let mut a = Scalar::from(10);
+        a += Scalar::from(5);
+        assert_eq!(a, Scalar::from(15));
+        a -= Scalar::from(3);
+        assert_eq!(a, Scalar::from(12));
+        a *= Scalar::from(4);
+        assert_eq!(a, Scalar::from(48));
+    }
+
+    #[test]
+    fn test_scalar_batch_invert() {
+        // each element times its inverse is 1 mod N, not mod P
+        let values = [2, 3, 0xFFFFFFFF];
+        let mut inverses = values.map(Scalar::from);
+        Scalar::batch_invert(&mut inverses);
+        for (value, inverse) in values.into_iter().zip(inverses) {
+            assert_eq!(Scalar::from(value) * inverse, Scalar::ONE);
+        }
+    }
+
+    #[test]
+    fn test_scalar_parse_and_format() {
+        let n_minus_one: Scalar =
+            "0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140"
+                .parse()
+                .unwrap();
+        assert_eq!(n_minus_one, -Scalar::ONE);
+        assert_eq!(
+            n_minus_one.to_string(),
+            "115792089237316195423570985008687907852837564279074904382605163141518161494336"
+        );
+        assert_eq!(format!("{:x}", Scalar::from(0xabc)), "abc");
+
+        // N itself is rejected
+        let n = "115792089237316195423570985008687907852837564279074904382605163141518161494337";
+        assert_eq!(n.parse::<Scalar>(), Err(ParseError::OutOfRange));
+    }
+
+    #[test]
+    fn test_scalar_from_bytes_reduced() {
+        assert_eq!(Scalar::from_bytes_reduced(N), Scalar::ZERO);
+
+        let mut n_plus_five = N;
+        n_plus_five[31] += 5;
+        assert_eq!(Scalar::from_bytes_reduced(n_plus_five), Scalar::from(5));
+
+        let mut seven = [0x00; 32];
+        seven[31] = 0x07;
+        assert_eq!(Scalar::from_bytes_reduced(seven), Scalar::from(7));
+
+        // 2^256 - 1 - N
+        let max: Scalar = "0x14551231950b75fc4402da1732fc9bebe".parse().unwrap();
+        assert_eq!(Scalar::from_bytes_reduced([0xFF; 32]), max);
+    }
+
+    #[test]
+    fn test_scalar_from_wide_bytes() {
+        assert_eq!(Scalar::from_wide_bytes([0x00; 64]), Scalar::ZERO);
+
+        // N * 2^256 + 3 = 3 mod N
+        let mut n_shifted = [0x00; 64];
+        n_shifted[..32].copy_from_slice(&N);
+        n_shifted[63] = 0x03;
+        assert_eq!(Scalar::from_wide_bytes(n_shifted), Scalar::from(3));
+
+        // the low half on its own matches from_bytes_reduced
+        let mut low_only = [0x00; 64];
+        low_only[32..].copy_from_slice(&[0xFF; 32]);
+        assert_eq!(
+            Scalar::from_wide_bytes(low_only),
+            Scalar::from_bytes_reduced([0xFF; 32])
+        );
+
+        let mut state = 0x510E527FADE682D1;
+        for _ in 0..100 {
+            let bytes: [u8; 64] = random_bytes(&mut state);
+            let expected = Scalar::from_bytes(reduce_modulus(bytes, N)).unwrap();
+            assert_eq!(Scalar::from_wide_bytes(bytes), expected);
+        }
+        let expected = Scalar::from_bytes(reduce_modulus([0xFF; 64], N)).unwrap();
+        assert_eq!(Scalar::from_wide_bytes([0xFF; 64]), expected);
+    }
+}
@@ -0,0 +1,596 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Shl, Shr};
+use std::str::FromStr;
+
+use crate::helpers::limbs::{
+    self, from_be_bytes, to_be_bytes, wide_from_be_bytes, wide_to_be_bytes,
+};
+use crate::helpers::utils::div_rem_limbs;
+use crate::{ArithmeticError, ParseError};
+
+/// A fixed-width unsigned integer of L 64-bit limbs
+///
+/// This is the public face of the limb helpers in helpers::limbs, use the
+/// U256 and U512 aliases rather than naming L directly. Values are plain
+/// integers, nothing is reduced mod P or N (see FieldElement and Scalar for that).
+///
+/// Limbs are stored in little endian order, while to_be_bytes/from_be_bytes
+/// use the big endian byte order of the rest of the crate.
+///
+/// Most methods are const fn, so derived constants can be computed at compile time.
+///
+/// NOTE: the comparisons from Ord return as soon as two limbs differ, so they
+/// are only meant for public values.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uint<const L: usize>([u64; L]);
+
+/// A 256-bit unsigned integer, e.g. a coordinate, a scalar or a hash
+pub type U256 = Uint<4>;
+
+/// A 512-bit unsigned integer, e.g. the full product of two U256
+pub type U512 = Uint<8>;
+
+impl<const L: usize> Uint<L> {
+    pub const ZERO: Self = Uint([0; L]);
+
+    pub const ONE: Self = {
+        let mut limbs = [0; L];
+        limbs[0] = 1;
+        Uint(limbs)
+    };
+
+    pub const MAX: Self = Uint([u64::MAX; L]);
+
+    /// The size of the integer in bits
+    pub const BITS: u32 = 64 * L as u32;
+
+    /// Creates an integer from little endian 64-bit limbs
+    pub const fn from_limbs(limbs: [u64; L]) -> Self {
+        Uint(limbs)
+    }
+
+    /// Returns the little endian 64-bit limbs
+    pub const fn to_limbs(&self) -> [u64; L] {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        limbs::is_zero(&self.0)
+    }
+
+    /// Returns bit i (bit 0 is the LSB), bits past the top are 0
+    pub const fn bit(&self, i: u32) -> bool {
+        i < Self::BITS && limbs::bit(&self.0, i as usize) == 1
+    }
+
+    /// The number of bits needed to hold the value, 0 for 0
+    pub const fn bits(&self) -> u32 {
+        let mut i = L;
+        while i > 0 {
+            i -= 1;
+            if self.0[i] != 0 {
+                return 64 * i as u32 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    /// self + rhs, and whether it overflowed (wrapped around at 2^BITS)
+    pub const fn overflowing_add(&self, rhs: &Self) -> (Self, bool) {
+        let (sum, carry) = limbs::add(&self.0, &rhs.0);
+        (Uint(sum), carry == 1)
+    }
+
+    /// self + rhs, or None if it doesn't fit
+    pub const fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    /// self + rhs mod 2^BITS
+    pub const fn wrapping_add(&self, rhs: &Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+
+    /// self - rhs, and whether it underflowed (wrapped around below 0)
+    pub const fn overflowing_sub(&self, rhs: &Self) -> (Self, bool) {
+        let (difference, borrow) = limbs::sub(&self.0, &rhs.0);
+        (Uint(difference), borrow == 1)
+    }
+
+    /// self - rhs, or None if rhs > self
+    pub const fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (difference, false) => Some(difference),
+            (_, true) => None,
+        }
+    }
+
+    /// self - rhs mod 2^BITS
+    pub const fn wrapping_sub(&self, rhs: &Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+
+    // self * multiplier + addend, returns the limb that overflowed out of the top
+    fn mul_add_small(&mut self, multiplier: u64, addend: u64) -> u64 {
+        let mut carry = addend;
+        for limb in self.0.iter_mut() {
+            (*limb, carry) = limbs::mac(0, *limb, multiplier, carry);
+        }
+        carry
+    }
+
+    // self / divisor, returns the remainder
+    fn div_rem_small(&mut self, divisor: u64) -> u64 {
+        let mut remainder = 0u128;
+        for limb in self.0.iter_mut().rev() {
+            let value = (remainder << 64) | *limb as u128;
+            *limb = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+        remainder as u64
+    }
+}
+
+impl U256 {
+    /// Creates an integer from 32 big endian bytes
+    pub const fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        Uint(from_be_bytes(&bytes))
+    }
+
+    /// Returns the value as 32 big endian bytes
+    pub const fn to_be_bytes(&self) -> [u8; 32] {
+        to_be_bytes(&self.0)
+    }
+
+    /// The full 512-bit product, which can't overflow
+    pub const fn widening_mul(&self, rhs: &U256) -> U512 {
+        Uint(limbs::mul_wide(&self.0, &rhs.0))
+    }
+}
+
+impl U512 {
+    /// Creates an integer from 64 big endian bytes
+    pub const fn from_be_bytes(bytes: [u8; 64]) -> Self {
+        Uint(wide_from_be_bytes(&bytes))
+    }
+
+    /// Returns the value as 64 big endian bytes
+    pub const fn to_be_bytes(&self) -> [u8; 64] {
+        wide_to_be_bytes(&self.0)
+    }
+
+    /// Splits the value into its (high, low) 256-bit halves
+    pub const fn split(&self) -> (U256, U256) {
+        let [l0, l1, l2, l3, h0, h1, h2, h3] = self.0;
+        (Uint([h0, h1, h2, h3]), Uint([l0, l1, l2, l3]))
+    }
+
+    /// Returns (q, r) where self = divisor * q + r, 0 <= r < divisor
+    ///
+    /// Returns DivisionByZero if the divisor is 0
+    pub const fn div_rem(&self, divisor: &U256) -> Result<(U512, U256), ArithmeticError> {
+        match div_rem_limbs(&self.0, &divisor.0) {
+            Ok((quotient, remainder)) => Ok((Uint(quotient), Uint(remainder))),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl<const L: usize> Default for Uint<L> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const L: usize> From<u64> for Uint<L> {
+    fn from(value: u64) -> Self {
+        let mut limbs = [0; L];
+        limbs[0] = value;
+        Uint(limbs)
+    }
+}
+
+// Zero extends a 256-bit integer
+impl From<U256> for U512 {
+    fn from(value: U256) -> Self {
+        let [l0, l1, l2, l3] = value.0;
+        Uint([l0, l1, l2, l3, 0, 0, 0, 0])
+    }
+}
+
+impl<const L: usize> Ord for Uint<L> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // the most significant limb is the last one
+        for i in (0..L).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl<const L: usize> PartialOrd for Uint<L> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parses a decimal integer, or a hex integer when prefixed with 0x
+///
+/// e.g. "115792089237316195423570985008687907853269984665640564039457584007908834671663"
+/// or "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f" are both P
+impl<const L: usize> FromStr for Uint<L> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => (hex, 16),
+            None => (s, 10),
+        };
+        if digits.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let mut result = Self::ZERO;
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or(ParseError::InvalidDigit)?;
+            // result = result * radix + digit, anything left over didn't fit
+            if result.mul_add_small(radix as u64, digit as u64) != 0 {
+                return Err(ParseError::Overflow);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl U256 {
+    /// Parses a value for a type that is always reduced mod the modulus
+    ///
+    /// Same syntax as from_str. Values >= modulus are rejected with
+    /// OutOfRange rather than reduced, a test vector that is out of range is
+    /// more likely a mistake than intended. FieldElement and Scalar parse
+    /// through this.
+    pub(crate) fn parse_below(s: &str, modulus: &[u64; 4]) -> Result<[u64; 4], ParseError> {
+        let value = s.parse::<U256>()?.0;
+        if limbs::is_greater_or_equal(&value, modulus) {
+            return Err(ParseError::OutOfRange);
+        }
+        Ok(value)
+    }
+}
+
+/// Formats the value in decimal
+impl<const L: usize> fmt::Display for Uint<L> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off 19 decimal digits at a time, the largest power of 10 in a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut value = *self;
+        let mut chunks = Vec::new();
+        loop {
+            chunks.push(value.div_rem_small(CHUNK));
+            if value.is_zero() {
+                break;
+            }
+        }
+
+        // the top chunk has no leading zeros, every other chunk is exactly 19 digits
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+/// Formats the value in lowercase hex, {:#x} adds the 0x prefix
+impl<const L: usize> fmt::LowerHex for Uint<L> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits = String::new();
+        for limb in self.0.iter().rev() {
+            if digits.is_empty() {
+                // skip leading zero limbs
+                if *limb != 0 {
+                    digits = format!("{:x}", limb);
+                }
+            } else {
+                digits.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        f.pad_integral(true, "0x", &digits)
+    }
+}
+
+// Shows every digit in hex, which is much easier to read than the limbs
+impl<const L: usize> fmt::Debug for Uint<L> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Uint({:#0width$x})", self, width = 16 * L + 2)
+    }
+}
+
+// Shifting by BITS or more shifts every bit out and gives 0
+impl<const L: usize> Shl<u32> for Uint<L> {
+    type Output = Self;
+
+    fn shl(self, shift: u32) -> Self {
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut result = [0u64; L];
+
+        for (i, limb) in result.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.0[i - limb_shift] << bit_shift;
+            // bits carried up from the limb below
+            if bit_shift > 0 && i > limb_shift {
+                *limb |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+
+        Uint(result)
+    }
+}
+
+impl<const L: usize> Shr<u32> for Uint<L> {
+    type Output = Self;
+
+    fn shr(self, shift: u32) -> Self {
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut result = [0u64; L];
+
+        for (i, limb) in result
+            .iter_mut()
+            .enumerate()
+            .take(L.saturating_sub(limb_shift))
+        {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            // bits carried down from the limb above
+            if bit_shift > 0 && i + limb_shift + 1 < L {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+
+        Uint(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::P;
+
+    #[test]
+    fn test_comparisons() {
+        // 256
+        let x1 = U256::from_be_bytes([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+        ]);
+        // 512
+        let y1 = U256::from_be_bytes([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x02, 0x00, 0x00,
+        ]);
+
+        let x2 = U256::from_be_bytes([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+        ]);
+
+        let y2 = U256::from_be_bytes([
+            0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00,
+        ]);
+
+        assert!(x1 < y1); // 256 < 512
+        assert!(y1 > x1); // 512 > 256
+        assert!(x2 >= x1); // 256 = 256
+        assert!(y2 > y1); // 1.1534 * 10**77  > 512
+        assert_eq!(x1.cmp(&x2), Ordering::Equal);
+        assert_eq!(U512::MAX.max(U512::ZERO), U512::MAX);
+    }
+
+    #[test]
+    fn test_add_and_sub() {
+        assert_eq!(
+            U256::from(3).checked_add(&U256::from(5)),
+            Some(U256::from(8))
+        );
+        assert_eq!(U256::MAX.checked_add(&U256::ONE), None);
+        assert_eq!(U256::MAX.wrapping_add(&U256::ONE), U256::ZERO);
+        assert_eq!(
+            U256::MAX.overflowing_add(&U256::MAX),
+            (U256::MAX.wrapping_sub(&U256::ONE), true)
+        );
+
+        assert_eq!(
+            U256::from(5).checked_sub(&U256::from(3)),
+            Some(U256::from(2))
+        );
+        assert_eq!(U256::ZERO.checked_sub(&U256::ONE), None);
+        assert_eq!(U256::ZERO.wrapping_sub(&U256::ONE), U256::MAX);
+    }
+
+    #[test]
+    fn test_simple_512_subtraction_with_carry() {
+        // 0x100 - 0x01 = 0xFF, borrowing from the second byte
+        let a = U512::from_be_bytes([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+        ]);
+        let b = U512::ONE;
+        let correct_result = U512::from(0xFF);
+        assert_eq!(a.wrapping_sub(&b), correct_result);
+        assert_eq!(a.checked_sub(&b), Some(correct_result));
+    }
+
+    #[test]
+    fn test_bit_and_bits() {
+        assert_eq!(U256::ZERO.bits(), 0);
+        assert_eq!(U256::ONE.bits(), 1);
+        assert_eq!(U256::from(0xFF).bits(), 8);
+        assert_eq!(U256::from_be_bytes(P).bits(), 256);
+        assert_eq!((U512::ONE << 300).bits(), 301);
+
+        let a = U256::from(0b1010);
+        assert!(!a.bit(0));
+        assert!(a.bit(1));
+        assert!(a.bit(3));
+        assert!(!a.bit(4));
+        assert!(!U256::MAX.bit(256));
+        assert!(U256::MAX.bit(255));
+    }
+
+    #[test]
+    fn test_shifts() {
+        let one = U256::ONE;
+        assert_eq!(one << 0, one);
+        assert_eq!(one << 64, U256::from_limbs([0, 1, 0, 0]));
+        assert_eq!(one << 255, U256::from_limbs([0, 0, 0, 1 << 63]));
+        assert_eq!(one << 256, U256::ZERO);
+
+        let a = U256::from_limbs([u64::MAX, 0, 0, 0]);
+        assert_eq!(a << 4, U256::from_limbs([u64::MAX << 4, 0xF, 0, 0]));
+        assert_eq!((a << 4) >> 4, a);
+        assert_eq!(U256::MAX >> 255, one);
+        assert_eq!(U256::MAX >> 256, U256::ZERO);
+        assert_eq!(
+            U256::MAX >> 100,
+            U256::from_limbs([u64::MAX, u64::MAX, 0xFFFFFFF, 0])
+        );
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        // (2^256 - 1)^2 = 2^512 - 2^257 + 1
+        let square = U256::MAX.widening_mul(&U256::MAX);
+        let (high, low) = square.split();
+        assert_eq!(low, U256::ONE);
+        assert_eq!(high, U256::MAX.wrapping_sub(&U256::ONE));
+
+        let p = U256::from_be_bytes(P);
+        assert_eq!(p.widening_mul(&U256::ONE), U512::from(p));
+    }
+
+    #[test]
+    fn test_const_evaluation() {
+        const P_SQUARED: U512 = U256::from_be_bytes(P).widening_mul(&U256::from_be_bytes(P));
+        const QUOTIENT_AND_REMAINDER: (U512, U256) = match P_SQUARED
+            .wrapping_add(&U512::ONE)
+            .div_rem(&U256::from_be_bytes(P))
+        {
+            Ok(result) => result,
+            Err(_) => panic!("division by zero"),
+        };
+        const BITS: u32 = P_SQUARED.bits();
+
+        assert_eq!(QUOTIENT_AND_REMAINDER.0, U512::from(U256::from_be_bytes(P)));
+        assert_eq!(QUOTIENT_AND_REMAINDER.1, U256::ONE);
+        assert_eq!(BITS, 512);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let p = U256::from_be_bytes(P);
+        let product = p.widening_mul(&U256::from(7)).wrapping_add(&U512::from(5));
+        assert_eq!(product.div_rem(&p).unwrap(), (U512::from(7), U256::from(5)));
+        assert!(product.div_rem(&U256::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal_and_hex() {
+        let p = U256::from_be_bytes(P);
+        let decimal =
+            "115792089237316195423570985008687907853269984665640564039457584007908834671663";
+        let hex = "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+        assert_eq!(decimal.parse::<U256>(), Ok(p));
+        assert_eq!(hex.parse::<U256>(), Ok(p));
+        assert_eq!(hex.to_uppercase().parse::<U256>(), Ok(p));
+
+        assert_eq!("0".parse::<U256>(), Ok(U256::ZERO));
+        assert_eq!("0x0".parse::<U256>(), Ok(U256::ZERO));
+        assert_eq!("255".parse::<U512>(), Ok(U512::from(0xFF)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<U256>(), Err(ParseError::Empty));
+        assert_eq!("0x".parse::<U256>(), Err(ParseError::Empty));
+        assert_eq!("12a".parse::<U256>(), Err(ParseError::InvalidDigit));
+        assert_eq!("0xfg".parse::<U256>(), Err(ParseError::InvalidDigit));
+        assert_eq!("-1".parse::<U256>(), Err(ParseError::InvalidDigit));
+
+        // 2^256 doesn't fit
+        let too_big = format!("0x1{}", "0".repeat(64));
+        assert_eq!(too_big.parse::<U256>(), Err(ParseError::Overflow));
+        assert!(too_big.parse::<U512>().is_ok());
+        let max_plus_one =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert_eq!(max_plus_one.parse::<U256>(), Err(ParseError::Overflow));
+    }
+
+    #[test]
+    fn test_format_decimal_and_hex() {
+        let p = U256::from_be_bytes(P);
+        assert_eq!(
+            p.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007908834671663"
+        );
+        assert_eq!(
+            format!("{:#x}", p),
+            "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"
+        );
+
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(format!("{:x}", U256::ZERO), "0");
+        assert_eq!(
+            format!("{:x}", U256::from_limbs([1, 1, 0, 0])),
+            "10000000000000001"
+        );
+        assert_eq!(format!("{:#06x}", U256::from(0xAB)), "0x00ab");
+        assert_eq!(
+            format!("{:?}", U256::ONE),
+            "Uint(0x0000000000000000000000000000000000000000000000000000000000000001)"
+        );
+
+        // 10^19 sits right on a chunk boundary
+        assert_eq!(
+            U256::from(10_000_000_000_000_000_000).to_string(),
+            "10000000000000000000"
+        );
+
+        // round trip
+        let mut state = 0x3C6EF372FE94F82B;
+        for _ in 0..20 {
+            let a = U512::from_be_bytes(crate::helpers::utils::random_bytes(&mut state));
+            assert_eq!(a.to_string().parse::<U512>(), Ok(a));
+            assert_eq!(format!("{:#x}", a).parse::<U512>(), Ok(a));
+        }
+    }
+
+    #[test]
+    fn test_byte_round_trip() {
+        assert_eq!(U256::from_be_bytes(P).to_be_bytes(), P);
+        let mut bytes = [0u8; 64];
+        bytes[0] = 0x80;
+        bytes[63] = 0x01;
+        assert_eq!(U512::from_be_bytes(bytes).to_be_bytes(), bytes);
+        assert!(U512::from_be_bytes(bytes).bit(511));
+    }
+}